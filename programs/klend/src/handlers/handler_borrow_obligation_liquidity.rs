@@ -11,7 +11,10 @@ use lending_checks::validate_referrer_token_state;
 use crate::{
     check_refresh_ixs, gen_signer_seeds,
     lending_market::{lending_checks, lending_operations},
-    state::{obligation::Obligation, CalculateBorrowResult, LendingMarket, Reserve},
+    state::{
+        obligation::Obligation, BorrowObligationLiquidityAccounts, CalculateBorrowResult,
+        LendingMarket, Reserve,
+    },
     utils::{seeds, token_transfer, FatAccountLoader},
     xmsg, LendingAction, LendingError, ReferrerTokenState, ReserveFarmKind,
 };
@@ -19,10 +22,15 @@ use crate::{
 pub fn process<'info>(
     ctx: Context<'_, '_, '_, 'info, BorrowObligationLiquidity<'info>>,
     liquidity_amount: u64,
+    max_borrow_fee: Option<u64>,
 ) -> Result<()> {
     msg!("liquidity_amount {}", liquidity_amount);
     check_refresh_ixs!(ctx, borrow_reserve, ReserveFarmKind::Debt);
-    lending_checks::borrow_obligation_liquidity_checks(&ctx)?;
+    lending_checks::borrow_obligation_liquidity_checks(&BorrowObligationLiquidityAccounts {
+        borrow_reserve: ctx.accounts.borrow_reserve.clone(),
+        borrow_reserve_liquidity_mint: ctx.accounts.borrow_reserve_liquidity_mint.clone(),
+        user_destination_liquidity: ctx.accounts.user_destination_liquidity.clone(),
+    })?;
 
     let borrow_reserve = &mut ctx.accounts.borrow_reserve.load_mut()?;
     let lending_market = &ctx.accounts.lending_market.load()?;
@@ -68,6 +76,7 @@ pub fn process<'info>(
     let CalculateBorrowResult {
         receive_amount,
         borrow_fee,
+        referrer_fee,
         ..
     } = lending_operations::borrow_obligation_liquidity(
         lending_market,
@@ -82,6 +91,18 @@ pub fn process<'info>(
 
     xmsg!("pnl: Borrow obligation liquidity {receive_amount} with borrow_fee {borrow_fee}",);
 
+    if let Some(max_borrow_fee) = max_borrow_fee {
+        if borrow_fee + referrer_fee > max_borrow_fee {
+            msg!(
+                "Borrow fee {} and referrer fee {} exceed the max borrow fee {}",
+                borrow_fee,
+                referrer_fee,
+                max_borrow_fee
+            );
+            return err!(LendingError::BorrowFeeExceedsMax);
+        }
+    }
+
     if borrow_fee > 0 {
         token_transfer::send_origination_fees_transfer(
             ctx.accounts.token_program.to_account_info(),
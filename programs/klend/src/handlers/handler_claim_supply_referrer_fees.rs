@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    fraction::FractionExtra,
+    lending_market::{lending_checks::validate_referrer_token_state, lending_operations},
+    state::{LendingMarket, Reserve},
+    ReferrerTokenState,
+};
+
+pub fn process(ctx: Context<ClaimSupplyReferrerFees>) -> Result<()> {
+    let reserve = &mut ctx.accounts.reserve.load_mut()?;
+    let referrer_token_state = &mut ctx.accounts.referrer_token_state.load_mut()?;
+
+    validate_referrer_token_state(
+        referrer_token_state,
+        ctx.accounts.referrer_token_state.key(),
+        reserve.liquidity.mint_pubkey,
+        reserve.config.supply_referrer,
+        ctx.accounts.reserve.key(),
+    )?;
+
+    let claimed_fees =
+        lending_operations::claim_supply_referrer_fees(reserve, referrer_token_state)?;
+
+    msg!(
+        "Claimed supply referrer fees {} for referrer {}",
+        claimed_fees.to_display(),
+        reserve.config.supply_referrer
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimSupplyReferrerFees<'info> {
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub reserve: AccountLoader<'info, Reserve>,
+
+    #[account(mut)]
+    pub referrer_token_state: AccountLoader<'info, ReferrerTokenState>,
+}
@@ -0,0 +1,394 @@
+use std::cell::RefMut;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::{instructions::Instructions as SysInstructions, SysvarId},
+    Accounts,
+};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+use lending_checks::validate_referrer_token_state;
+
+use crate::{
+    check_refresh_ixs, gen_signer_seeds,
+    lending_market::{lending_checks, lending_operations},
+    state::{
+        nested_accounts::*, obligation::Obligation, CalculateBorrowResult, LendingMarket, Reserve,
+    },
+    utils::{seeds, token_transfer, FatAccountLoader},
+    xmsg, LendingAction, LendingError, ReferrerTokenState, ReserveFarmKind,
+};
+
+/// Deposits liquidity as obligation collateral and borrows against it in one instruction, without
+/// a refresh_obligation in between.
+///
+/// The remaining accounts are the ones refresh_obligation takes for the obligation as it is before
+/// the deposit: its deposit reserves, then its borrow reserves, then the referrer token states of
+/// its borrows when it has a referrer. A deposit reserve that is not yet part of the obligation is
+/// not listed there, it is taken from `deposit_reserve`.
+pub fn process<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositAndBorrow<'info>>,
+    liquidity_amount: u64,
+    borrow_amount: u64,
+    max_borrow_fee: u64,
+) -> Result<()> {
+    check_refresh_ixs!(
+        ctx,
+        deposit_reserve,
+        borrow_reserve,
+        ReserveFarmKind::Collateral,
+        ReserveFarmKind::Debt
+    );
+    msg!(
+        "DepositAndBorrow deposit reserve {} amount {} borrow reserve {} amount {}",
+        ctx.accounts.deposit_reserve.key(),
+        liquidity_amount,
+        ctx.accounts.borrow_reserve.key(),
+        borrow_amount
+    );
+
+    lending_checks::deposit_reserve_liquidity_and_obligation_collateral_checks(
+        &DepositReserveLiquidityAndObligationCollateralAccounts {
+            user_source_liquidity: ctx.accounts.user_source_liquidity.clone(),
+            reserve: ctx.accounts.deposit_reserve.clone(),
+            reserve_liquidity_mint: ctx.accounts.deposit_reserve_liquidity_mint.clone(),
+        },
+    )?;
+    lending_checks::borrow_obligation_liquidity_checks(&BorrowObligationLiquidityAccounts {
+        borrow_reserve: ctx.accounts.borrow_reserve.clone(),
+        borrow_reserve_liquidity_mint: ctx.accounts.borrow_reserve_liquidity_mint.clone(),
+        user_destination_liquidity: ctx.accounts.user_destination_liquidity.clone(),
+    })?;
+
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+    let lending_market = &ctx.accounts.lending_market.load()?;
+    let lending_market_key = ctx.accounts.lending_market.key();
+    let clock = &Clock::get()?;
+
+    let authority_signer_seeds =
+        gen_signer_seeds!(lending_market_key.as_ref(), lending_market.bump_seed as u8);
+
+    let deposit_reserve_key = ctx.accounts.deposit_reserve.key();
+    let deposit_count = obligation.deposits_count();
+    let borrow_count = obligation.borrows_count();
+    let reserves_count = deposit_count + borrow_count;
+
+    let expected_remaining_accounts = if obligation.has_referrer() {
+        reserves_count + borrow_count
+    } else {
+        reserves_count
+    };
+
+    if ctx.remaining_accounts.len() != expected_remaining_accounts {
+        msg!(
+            "expected_remaining_accounts={} obligation.has_referrer()={} reserves_count={} borrow_count={}",
+            expected_remaining_accounts,
+            obligation.has_referrer(),
+            reserves_count,
+            borrow_count
+        );
+        return err!(LendingError::InvalidAccountInput);
+    }
+
+    let is_new_deposit = !obligation
+        .deposits
+        .iter()
+        .any(|collateral| collateral.deposit_reserve == deposit_reserve_key);
+
+    {
+        let deposit_reserve = &mut ctx.accounts.deposit_reserve.load_mut()?;
+
+        let initial_reserve_token_balance = token_interface::accessor::amount(
+            &ctx.accounts
+                .deposit_reserve_liquidity_supply
+                .to_account_info(),
+        )?;
+        let initial_reserve_available_liquidity = deposit_reserve.liquidity.available_amount;
+        let collateral_amount =
+            lending_operations::deposit_reserve_liquidity_and_obligation_collateral(
+                lending_market,
+                deposit_reserve,
+                obligation,
+                clock,
+                liquidity_amount,
+                deposit_reserve_key,
+            )?;
+
+        msg!(
+            "pnl: Deposit reserve liquidity {} and obligation collateral {}",
+            liquidity_amount,
+            collateral_amount
+        );
+
+        token_transfer::deposit_reserve_liquidity_and_obligation_collateral_transfer(
+            ctx.accounts.user_source_liquidity.to_account_info(),
+            ctx.accounts
+                .deposit_reserve_liquidity_supply
+                .to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts
+                .deposit_reserve_liquidity_mint
+                .to_account_info(),
+            ctx.accounts
+                .deposit_liquidity_token_program
+                .to_account_info(),
+            ctx.accounts
+                .deposit_reserve_collateral_mint
+                .to_account_info(),
+            ctx.accounts
+                .deposit_reserve_destination_collateral
+                .to_account_info(),
+            ctx.accounts.collateral_token_program.to_account_info(),
+            ctx.accounts.lending_market_authority.clone(),
+            authority_signer_seeds,
+            liquidity_amount,
+            ctx.accounts.deposit_reserve_liquidity_mint.decimals,
+            collateral_amount,
+        )?;
+
+        lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+            token_interface::accessor::amount(
+                &ctx.accounts
+                    .deposit_reserve_liquidity_supply
+                    .to_account_info(),
+            )
+            .unwrap(),
+            deposit_reserve.liquidity.available_amount,
+            initial_reserve_token_balance,
+            initial_reserve_available_liquidity,
+            LendingAction::Additive(liquidity_amount),
+        )?;
+
+        // The deposit marks the reserve stale, refresh it with its saved price for refresh_obligation
+        lending_operations::refresh_reserve(
+            deposit_reserve,
+            clock,
+            None,
+            lending_market.referral_fee_bps,
+        )?;
+    }
+
+    let mut deposit_reserve_infos = ctx.remaining_accounts[..deposit_count].to_vec();
+    if is_new_deposit {
+        let new_deposit_position = obligation
+            .deposits
+            .iter()
+            .filter(|collateral| collateral.deposit_reserve != Pubkey::default())
+            .position(|collateral| collateral.deposit_reserve == deposit_reserve_key)
+            .unwrap();
+        deposit_reserve_infos.insert(
+            new_deposit_position,
+            ctx.accounts.deposit_reserve.to_account_info(),
+        );
+    }
+
+    let deposit_reserves_iter = deposit_reserve_infos
+        .iter()
+        .map(|account_info| FatAccountLoader::<Reserve>::try_from(account_info).unwrap());
+
+    lending_operations::refresh_obligation(
+        obligation,
+        lending_market,
+        clock.slot,
+        deposit_reserves_iter.clone(),
+        ctx.remaining_accounts
+            .iter()
+            .skip(deposit_count)
+            .take(borrow_count)
+            .map(|account_info| FatAccountLoader::<Reserve>::try_from(account_info).unwrap()),
+        ctx.remaining_accounts
+            .iter()
+            .skip(reserves_count)
+            .map(|account_info| {
+                FatAccountLoader::<ReferrerTokenState>::try_from(account_info).unwrap()
+            }),
+    )?;
+
+    let borrow_reserve = &mut ctx.accounts.borrow_reserve.load_mut()?;
+
+    let referrer_token_state_option: Option<RefMut<ReferrerTokenState>> =
+        if obligation.has_referrer() {
+            match &ctx.accounts.referrer_token_state {
+                Some(referrer_token_state_loader) => {
+                    let referrer_token_state = referrer_token_state_loader.load_mut()?;
+
+                    validate_referrer_token_state(
+                        &referrer_token_state,
+                        referrer_token_state_loader.key(),
+                        borrow_reserve.liquidity.mint_pubkey,
+                        obligation.referrer,
+                        ctx.accounts.borrow_reserve.key(),
+                    )?;
+
+                    Some(referrer_token_state)
+                }
+                None => return err!(LendingError::ReferrerAccountMissing),
+            }
+        } else {
+            None
+        };
+
+    let initial_reserve_token_balance = token_interface::accessor::amount(
+        &ctx.accounts
+            .borrow_reserve_source_liquidity
+            .to_account_info(),
+    )?;
+    let initial_reserve_available_liquidity = borrow_reserve.liquidity.available_amount;
+
+    let CalculateBorrowResult {
+        receive_amount,
+        borrow_fee,
+        referrer_fee,
+        ..
+    } = lending_operations::borrow_obligation_liquidity(
+        lending_market,
+        borrow_reserve,
+        obligation,
+        borrow_amount,
+        clock,
+        ctx.accounts.borrow_reserve.key(),
+        referrer_token_state_option,
+        deposit_reserves_iter,
+    )?;
+
+    xmsg!("pnl: Borrow obligation liquidity {receive_amount} with borrow_fee {borrow_fee}",);
+
+    if borrow_fee + referrer_fee > max_borrow_fee {
+        msg!(
+            "Borrow fee {} and referrer fee {} exceed the max borrow fee {}",
+            borrow_fee,
+            referrer_fee,
+            max_borrow_fee
+        );
+        return err!(LendingError::BorrowFeeExceedsMax);
+    }
+
+    if borrow_fee > 0 {
+        token_transfer::send_origination_fees_transfer(
+            ctx.accounts
+                .borrow_liquidity_token_program
+                .to_account_info(),
+            ctx.accounts.borrow_reserve_liquidity_mint.to_account_info(),
+            ctx.accounts
+                .borrow_reserve_source_liquidity
+                .to_account_info(),
+            ctx.accounts
+                .borrow_reserve_liquidity_fee_receiver
+                .to_account_info(),
+            ctx.accounts.lending_market_authority.to_account_info(),
+            authority_signer_seeds,
+            borrow_fee,
+            ctx.accounts.borrow_reserve_liquidity_mint.decimals,
+        )?;
+    }
+
+    token_transfer::borrow_obligation_liquidity_transfer(
+        ctx.accounts
+            .borrow_liquidity_token_program
+            .to_account_info(),
+        ctx.accounts.borrow_reserve_liquidity_mint.to_account_info(),
+        ctx.accounts
+            .borrow_reserve_source_liquidity
+            .to_account_info(),
+        ctx.accounts.user_destination_liquidity.to_account_info(),
+        ctx.accounts.lending_market_authority.to_account_info(),
+        authority_signer_seeds,
+        receive_amount,
+        ctx.accounts.borrow_reserve_liquidity_mint.decimals,
+    )?;
+
+    lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+        token_interface::accessor::amount(
+            &ctx.accounts
+                .borrow_reserve_source_liquidity
+                .to_account_info(),
+        )
+        .unwrap(),
+        borrow_reserve.liquidity.available_amount,
+        initial_reserve_token_balance,
+        initial_reserve_available_liquidity,
+        LendingAction::Subtractive(borrow_fee + receive_amount),
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositAndBorrow<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = owner @ LendingError::InvalidObligationOwner
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+    #[account(
+        seeds = [seeds::LENDING_MARKET_AUTH, lending_market.key().as_ref()],
+        bump = lending_market.load()?.bump_seed as u8,
+    )]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    #[account(mut, has_one = lending_market)]
+    pub deposit_reserve: AccountLoader<'info, Reserve>,
+
+    #[account(mut,
+        address = deposit_reserve.load()?.liquidity.mint_pubkey,
+        mint::token_program = deposit_liquidity_token_program,
+    )]
+    pub deposit_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        address = deposit_reserve.load()?.liquidity.supply_vault,
+    )]
+    pub deposit_reserve_liquidity_supply: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = deposit_reserve.load()?.collateral.mint_pubkey)]
+    pub deposit_reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = deposit_reserve.load()?.collateral.supply_vault)]
+    pub deposit_reserve_destination_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = deposit_reserve.load()?.liquidity.mint_pubkey,
+        token::authority = owner,
+    )]
+    pub user_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, has_one = lending_market)]
+    pub borrow_reserve: AccountLoader<'info, Reserve>,
+
+    #[account(mut,
+        address = borrow_reserve.load()?.liquidity.mint_pubkey,
+        mint::token_program = borrow_liquidity_token_program,
+    )]
+    pub borrow_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        address = borrow_reserve.load()?.liquidity.supply_vault
+    )]
+    pub borrow_reserve_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        address = borrow_reserve.load()?.liquidity.fee_vault
+    )]
+    pub borrow_reserve_liquidity_fee_receiver: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = borrow_reserve_source_liquidity.mint,
+        token::authority = owner,
+    )]
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub referrer_token_state: Option<AccountLoader<'info, ReferrerTokenState>>,
+
+    pub collateral_token_program: Program<'info, Token>,
+    pub deposit_liquidity_token_program: Interface<'info, TokenInterface>,
+    pub borrow_liquidity_token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = SysInstructions::id())]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+}
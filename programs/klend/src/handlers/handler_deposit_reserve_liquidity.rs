@@ -9,9 +9,10 @@ use lending_operations::refresh_reserve;
 
 use crate::{
     gen_signer_seeds,
+    handlers::handler_refresh_reserves_batch::maybe_price_account,
     lending_market::{lending_checks, lending_operations},
     state::{LendingMarket, Reserve},
-    utils::{seeds, token_transfer},
+    utils::{prices::get_price, seeds, token_transfer},
     LendingAction,
 };
 
@@ -39,7 +40,38 @@ pub fn process(ctx: Context<DepositReserveLiquidity>, liquidity_amount: u64) ->
     let authority_signer_seeds =
         gen_signer_seeds!(lending_market_key.as_ref(), lending_market.bump_seed as u8);
 
-    refresh_reserve(reserve, &clock, None, lending_market.referral_fee_bps)?;
+    let price_res = if !ctx.remaining_accounts.is_empty()
+        && lending_operations::is_price_refresh_needed(
+            reserve,
+            lending_market,
+            clock.unix_timestamp,
+        ) {
+        let mut price_accounts_it = ctx.remaining_accounts.iter();
+        let pyth_oracle = maybe_price_account(price_accounts_it.next())?;
+        let switchboard_price_oracle = maybe_price_account(price_accounts_it.next())?;
+        let switchboard_twap_oracle = maybe_price_account(price_accounts_it.next())?;
+        let scope_prices = maybe_price_account(price_accounts_it.next())?;
+
+        reserve.config.token_info.validate_token_info_config(
+            pyth_oracle,
+            switchboard_price_oracle,
+            switchboard_twap_oracle,
+            scope_prices,
+        )?;
+
+        get_price(
+            &reserve.config.token_info,
+            pyth_oracle,
+            switchboard_price_oracle,
+            switchboard_twap_oracle,
+            scope_prices,
+            clock.unix_timestamp,
+        )?
+    } else {
+        None
+    };
+
+    refresh_reserve(reserve, &clock, price_res, lending_market.referral_fee_bps)?;
 
     let initial_reserve_token_balance = token_interface::accessor::amount(
         &ctx.accounts.reserve_liquidity_supply.to_account_info(),
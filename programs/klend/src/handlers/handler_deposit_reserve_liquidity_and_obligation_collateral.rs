@@ -1,6 +1,9 @@
 use anchor_lang::{
     prelude::*,
-    solana_program::sysvar::{instructions::Instructions as SysInstructions, SysvarId},
+    solana_program::{
+        program::set_return_data,
+        sysvar::{instructions::Instructions as SysInstructions, SysvarId},
+    },
     Accounts,
 };
 use anchor_spl::token::Token;
@@ -10,8 +13,8 @@ use crate::{
     check_refresh_ixs, gen_signer_seeds,
     lending_market::{lending_checks, lending_operations},
     state::{nested_accounts::*, obligation::Obligation, LendingMarket, Reserve},
-    utils::{seeds, token_transfer},
-    LendingAction, ReserveFarmKind,
+    utils::{seeds, token_transfer, Fraction},
+    DepositLiquidityResult, LendingAction, ReserveFarmKind,
 };
 
 pub fn process(
@@ -48,18 +51,14 @@ pub fn process(
     )?;
     let initial_reserve_available_liquidity = reserve.liquidity.available_amount;
     let collateral_amount =
-        lending_operations::deposit_reserve_liquidity(reserve, &clock, liquidity_amount)?;
-
-    lending_operations::refresh_reserve(reserve, &clock, None, lending_market.referral_fee_bps)?;
-
-    lending_operations::deposit_obligation_collateral(
-        lending_market,
-        reserve,
-        obligation,
-        clock.slot,
-        collateral_amount,
-        ctx.accounts.reserve.key(),
-    )?;
+        lending_operations::deposit_reserve_liquidity_and_obligation_collateral(
+            lending_market,
+            reserve,
+            obligation,
+            &clock,
+            liquidity_amount,
+            ctx.accounts.reserve.key(),
+        )?;
 
     msg!(
         "pnl: Deposit reserve liquidity {} and obligation collateral {}",
@@ -94,6 +93,13 @@ pub fn process(
         LendingAction::Additive(liquidity_amount),
     )?;
 
+    let result = DepositLiquidityResult {
+        collateral_amount,
+        collateral_exchange_rate_sf: Fraction::from(reserve.collateral_exchange_rate()?).to_bits(),
+        total_supply_sf: reserve.liquidity.total_supply()?.to_bits(),
+    };
+    set_return_data(&result.try_to_vec()?);
+
     Ok(())
 }
 
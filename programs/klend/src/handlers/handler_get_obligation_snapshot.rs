@@ -0,0 +1,18 @@
+use anchor_lang::{prelude::*, solana_program::program::set_return_data};
+
+use crate::{lending_market::lending_operations, state::obligation::Obligation};
+
+pub fn process(ctx: Context<GetObligationSnapshot>) -> Result<()> {
+    let obligation = &ctx.accounts.obligation.load()?;
+    let clock = Clock::get()?;
+
+    let snapshot = lending_operations::get_obligation_snapshot(obligation, clock.slot)?;
+    set_return_data(&snapshot.try_to_vec()?);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct GetObligationSnapshot<'info> {
+    pub obligation: AccountLoader<'info, Obligation>,
+}
@@ -0,0 +1,31 @@
+use anchor_lang::{prelude::*, solana_program::program::set_return_data};
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{lending_market::lending_operations, state::Reserve};
+
+pub fn process(ctx: Context<GetReserveVaultBalanceSnapshot>) -> Result<()> {
+    let reserve = &ctx.accounts.reserve.load()?;
+
+    let snapshot = lending_operations::get_reserve_vault_balance_snapshot(
+        reserve,
+        ctx.accounts.reserve_liquidity_supply.amount,
+    )?;
+    msg!(
+        "Reserve {} vault balance {} available amount {} drift {}",
+        ctx.accounts.reserve.key(),
+        snapshot.vault_balance,
+        snapshot.available_amount,
+        snapshot.drift
+    );
+    set_return_data(&snapshot.try_to_vec()?);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct GetReserveVaultBalanceSnapshot<'info> {
+    pub reserve: AccountLoader<'info, Reserve>,
+
+    #[account(address = reserve.load()?.liquidity.supply_vault)]
+    pub reserve_liquidity_supply: Box<InterfaceAccount<'info, TokenAccount>>,
+}
@@ -24,6 +24,13 @@ pub fn process(ctx: Context<InitFarmsForReserve>, mode: u8) -> Result<()> {
 
     farms_ixs::cpi_initialize_farm_delegated(&ctx)?;
 
+    farms_ixs::validate_reserve_farm_state(
+        &ctx.accounts.farm_state,
+        reserve,
+        ctx.accounts.lending_market_authority.key,
+        mode,
+    )?;
+
     Ok(())
 }
 
@@ -0,0 +1,108 @@
+use anchor_lang::{prelude::*, Accounts};
+use farms::program::Farms;
+
+use crate::{
+    lending_market::farms_ixs,
+    state::{obligation::Obligation, LendingMarket},
+    utils::{seeds, FatAccountLoader, PROGRAM_VERSION},
+    LendingError, Reserve, ReserveStatus,
+};
+
+pub fn process<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitObligationFarmsForReservesBatch<'info>>,
+    modes: Vec<u8>,
+) -> Result<()> {
+    let obligation = ctx.accounts.obligation.key();
+    let lending_market_key = ctx.accounts.lending_market.key();
+
+    require!(
+        ctx.remaining_accounts.len() == modes.len() * 3,
+        LendingError::InvalidAccountInput
+    );
+
+    for (accounts, mode) in ctx.remaining_accounts.chunks_exact(3).zip(modes) {
+        let [reserve_acc, reserve_farm_state, obligation_farm] = accounts else {
+            unreachable!()
+        };
+
+        let reserve_loader = FatAccountLoader::<Reserve>::try_from(reserve_acc)?;
+        let reserve = reserve_loader.load()?;
+
+        require_keys_eq!(
+            reserve.lending_market,
+            lending_market_key,
+            LendingError::InvalidAccountInput
+        );
+
+        let farm = reserve.get_farm(mode.try_into().unwrap());
+        if farm == Pubkey::default() {
+            msg!(
+                "Reserve {} has no farm for mode {}, skipping",
+                reserve_acc.key,
+                mode
+            );
+            continue;
+        }
+
+        require!(
+            reserve.config.status() != ReserveStatus::Obsolete,
+            LendingError::ReserveObsolete
+        );
+        require!(
+            reserve.version == PROGRAM_VERSION as u64,
+            LendingError::ReserveDeprecated
+        );
+        require_keys_eq!(
+            farm,
+            reserve_farm_state.key(),
+            LendingError::InvalidAccountInput
+        );
+
+        if obligation_farm.owner == &ctx.accounts.farms_program.key()
+            && !obligation_farm.data_is_empty()
+        {
+            msg!(
+                "Obligation farm {} already initialized, skipping",
+                obligation_farm.key
+            );
+            continue;
+        }
+
+        farms_ixs::cpi_initialize_farmer_delegated_batch(
+            &ctx,
+            &obligation,
+            reserve_farm_state,
+            obligation_farm,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitObligationFarmsForReservesBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub owner: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        has_one = lending_market,
+        has_one = owner
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    #[account(
+        mut,
+        seeds = [seeds::LENDING_MARKET_AUTH, lending_market.key().as_ref()],
+        bump = lending_market.load()?.bump_seed as u8,
+    )]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    pub farms_program: Program<'info, Farms>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
@@ -9,7 +9,7 @@ use crate::{
             InitReserveParams, NewReserveCollateralParams, NewReserveLiquidityParams,
             ReserveCollateral, ReserveLiquidity,
         },
-        LendingMarket, Reserve, ReserveConfig,
+        LendingMarket, Reserve, ReserveConfig, TokenInfo,
     },
     utils::seeds,
     LendingError, ReserveStatus,
@@ -23,6 +23,8 @@ pub fn process<'info>(ctx: Context<'_, '_, '_, 'info, InitReserve<'info>>) -> Re
         &ctx.accounts.reserve_liquidity_supply.to_account_info(),
     )?;
 
+    let quote_currency = ctx.accounts.lending_market.load()?.quote_currency;
+
     reserve.init(InitReserveParams {
         current_slot: clock.slot,
         lending_market: ctx.accounts.lending_market.key(),
@@ -39,7 +41,12 @@ pub fn process<'info>(ctx: Context<'_, '_, '_, 'info, InitReserve<'info>>) -> Re
             supply_vault: ctx.accounts.reserve_collateral_supply.key(),
         })),
         config: Box::new(ReserveConfig {
+            // No new obligation positions can be opened until the owner sets the reserve Active
             status: ReserveStatus::Hidden.into(),
+            token_info: TokenInfo {
+                quote_currency,
+                ..Default::default()
+            },
             ..Default::default()
         }),
     });
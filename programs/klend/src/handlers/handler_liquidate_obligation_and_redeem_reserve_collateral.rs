@@ -1,6 +1,9 @@
 use anchor_lang::{
     prelude::*,
-    solana_program::sysvar::{instructions::Instructions as SysInstructions, SysvarId},
+    solana_program::{
+        program::set_return_data,
+        sysvar::{instructions::Instructions as SysInstructions, SysvarId},
+    },
     Accounts,
 };
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
@@ -11,7 +14,7 @@ use crate::{
     lending_market::{lending_checks, lending_operations},
     state::{obligation::Obligation, LendingMarket, RedeemReserveCollateralAccounts, Reserve},
     utils::{seeds, token_transfer, FatAccountLoader},
-    xmsg, LendingAction, LiquidateAndRedeemResult, ReserveFarmKind,
+    xmsg, LendingAction, LiquidateAndRedeemResult, LiquidateAndRedeemReturnData, ReserveFarmKind,
 };
 
 pub fn process(
@@ -19,6 +22,7 @@ pub fn process(
     liquidity_amount: u64,
     min_acceptable_received_liquidity_amount: u64,
     max_allowed_ltv_override_percent: u64,
+    cap_to_redeemable_collateral: bool,
 ) -> Result<()> {
     xmsg!(
         "LiquidateObligationAndRedeemReserveCollateral amount {} max_allowed_ltv_override_percent {}",
@@ -93,16 +97,18 @@ pub fn process(
         withdraw_collateral_amount,
         withdraw_amount,
         total_withdraw_liquidity_amount,
-        ..
+        liquidation_reason,
     } = lending_operations::liquidate_and_redeem(
         lending_market,
         &ctx.accounts.repay_reserve,
         &ctx.accounts.withdraw_reserve,
         obligation,
+        &ctx.accounts.liquidator.key(),
         clock,
         liquidity_amount,
         min_acceptable_received_liquidity_amount,
         max_allowed_ltv_override_pct_opt,
+        cap_to_redeemable_collateral,
         ctx.remaining_accounts.iter().map(|a| {
             FatAccountLoader::try_from(a).expect("Remaining account is not a valid deposit reserve")
         }),
@@ -236,6 +242,21 @@ pub fn process(
         )?;
     }
 
+    let (protocol_liquidation_fee, net_withdraw_liquidity_amount) = total_withdraw_liquidity_amount
+        .map(|(withdraw_liquidity_amount, protocol_fee)| {
+            (protocol_fee, withdraw_liquidity_amount - protocol_fee)
+        })
+        .unwrap_or((0, 0));
+
+    let result = LiquidateAndRedeemReturnData {
+        repay_amount,
+        withdraw_collateral_amount,
+        protocol_liquidation_fee,
+        net_withdraw_liquidity_amount,
+        liquidation_reason,
+    };
+    set_return_data(&result.try_to_vec()?);
+
     Ok(())
 }
 
@@ -0,0 +1,150 @@
+use anchor_lang::{prelude::*, Accounts};
+use farms::{program::Farms, state::UserState as FarmsUserState};
+
+use crate::{
+    handlers::handler_init_obligation::check_obligation_seeds,
+    lending_market::farms_ixs,
+    state::{
+        obligation::{InitObligationParams, Obligation, ObligationCollateral, ObligationLiquidity},
+        LendingMarket,
+    },
+    utils::{consts::OBLIGATION_SIZE, seeds},
+    InitObligationArgs, LendingError,
+};
+
+/// Remaining accounts hold one `[reserve_farm_state, obligation_farm, new_obligation_farm]`
+/// triplet per farm the obligation is registered in. The old farm user state is refreshed to a
+/// zero stake and a user state delegated to the new obligation is created in the same farm.
+pub fn process<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigrateObligationSeeds<'info>>,
+    args: InitObligationArgs,
+) -> Result<()> {
+    let clock = &Clock::get()?;
+
+    require!(args.id == 0, LendingError::InvalidObligationId);
+
+    check_obligation_seeds(
+        args.tag,
+        &ctx.accounts.seed1_account,
+        &ctx.accounts.seed2_account,
+    )?;
+
+    {
+        let obligation = &ctx.accounts.obligation.load()?;
+        if !obligation.deposits_empty() || !obligation.borrows_empty() {
+            msg!("Only empty obligations can be migrated to new seeds");
+            return err!(LendingError::ObligationNotEmpty);
+        }
+
+        let new_obligation = &mut ctx.accounts.new_obligation.load_init()?;
+        new_obligation.init(InitObligationParams {
+            current_slot: clock.slot,
+            lending_market: ctx.accounts.lending_market.key(),
+            owner: ctx.accounts.obligation_owner.key(),
+            deposits: [ObligationCollateral::default(); 8],
+            borrows: [ObligationLiquidity::default(); 5],
+            tag: args.tag as u64,
+            referrer: obligation.referrer,
+        });
+        new_obligation.self_borrow_limit_value = obligation.self_borrow_limit_value;
+        new_obligation.isolated_mode = obligation.isolated_mode;
+
+        msg!(
+            "Migrated obligation {} with tag {} to {} with tag {}",
+            ctx.accounts.obligation.key(),
+            obligation.tag,
+            ctx.accounts.new_obligation.key(),
+            args.tag
+        );
+    }
+
+    migrate_obligation_farms(&ctx)
+}
+
+fn migrate_obligation_farms<'info>(
+    ctx: &Context<'_, '_, '_, 'info, MigrateObligationSeeds<'info>>,
+) -> Result<()> {
+    let farm_accounts = ctx.remaining_accounts;
+    if farm_accounts.len() % 3 != 0 {
+        msg!("Farm accounts must be passed as [farm state, obligation farm, new obligation farm]");
+        return err!(LendingError::InvalidAccountInput);
+    }
+
+    for farm_accounts in farm_accounts.chunks_exact(3) {
+        let farm_state = &farm_accounts[0];
+        let obligation_farm = &farm_accounts[1];
+        let new_obligation_farm = &farm_accounts[2];
+
+        let active_stake_scaled = {
+            let obligation_farm_loader =
+                AccountLoader::<FarmsUserState>::try_from(obligation_farm)?;
+            let obligation_farm_user_state = obligation_farm_loader.load()?;
+            require_keys_eq!(
+                obligation_farm_user_state.delegatee,
+                ctx.accounts.obligation.key(),
+                LendingError::InvalidAccountInput
+            );
+            obligation_farm_user_state.active_stake_scaled
+        };
+
+        if active_stake_scaled != 0 {
+            farms_ixs::cpi_set_stake_delegated_for_migration(ctx, farm_state, obligation_farm, 0)?;
+        }
+
+        farms_ixs::cpi_initialize_farmer_delegated_for_migration(
+            ctx,
+            farm_state,
+            new_obligation_farm,
+        )?;
+
+        msg!(
+            "Migrated farm {} user state {} to {}",
+            farm_state.key(),
+            obligation_farm.key(),
+            new_obligation_farm.key()
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(args: InitObligationArgs)]
+pub struct MigrateObligationSeeds<'info> {
+    #[account(mut)]
+    pub obligation_owner: Signer<'info>,
+
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        constraint = obligation.load()?.owner == obligation_owner.key() @ LendingError::InvalidObligationOwner,
+        close = obligation_owner,
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    #[account(init,
+        seeds = [&[args.tag], &[args.id], obligation_owner.key().as_ref(), lending_market.key().as_ref(), seed1_account.key().as_ref(), seed2_account.key().as_ref()],
+        bump,
+        payer = fee_payer,
+        space = OBLIGATION_SIZE + 8,
+    )]
+    pub new_obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(
+        mut,
+        seeds = [seeds::LENDING_MARKET_AUTH, lending_market.key().as_ref()],
+        bump = lending_market.load()?.bump_seed as u8,
+    )]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    pub seed1_account: AccountInfo<'info>,
+    pub seed2_account: AccountInfo<'info>,
+
+    pub farms_program: Program<'info, Farms>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,145 @@
+use anchor_lang::{prelude::*, Accounts};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    gen_signer_seeds,
+    lending_market::{lending_checks, lending_operations},
+    state::{LendingMarket, Reserve},
+    utils::{constraints, seeds, token_transfer},
+    LendingAction, LendingError,
+};
+
+pub fn process(ctx: Context<RebalanceReserveLiquidity>, amount: u64) -> Result<()> {
+    constraints::token_2022::validate_liquidity_token_extensions(
+        &ctx.accounts.reserve_liquidity_mint.to_account_info(),
+        &ctx.accounts
+            .source_reserve_supply_liquidity
+            .to_account_info(),
+    )?;
+
+    require_keys_neq!(
+        ctx.accounts.source_reserve.key(),
+        ctx.accounts.destination_reserve.key(),
+        LendingError::InvalidAccountInput
+    );
+
+    let clock = &Clock::get()?;
+
+    let source_reserve = &mut ctx.accounts.source_reserve.load_mut()?;
+    let destination_reserve = &mut ctx.accounts.destination_reserve.load_mut()?;
+    let lending_market = &ctx.accounts.lending_market.load()?;
+    let lending_market_key = ctx.accounts.lending_market.key();
+
+    let authority_signer_seeds =
+        gen_signer_seeds!(lending_market_key, lending_market.bump_seed as u8);
+
+    let initial_source_vault_balance = token_interface::accessor::amount(
+        &ctx.accounts
+            .source_reserve_supply_liquidity
+            .to_account_info(),
+    )?;
+    let initial_source_available_liquidity = source_reserve.liquidity.available_amount;
+    let initial_destination_vault_balance = token_interface::accessor::amount(
+        &ctx.accounts
+            .destination_reserve_supply_liquidity
+            .to_account_info(),
+    )?;
+    let initial_destination_available_liquidity = destination_reserve.liquidity.available_amount;
+
+    let rebalance_amount = lending_operations::rebalance_protocol_liquidity(
+        source_reserve,
+        destination_reserve,
+        amount,
+        clock.slot,
+    )?;
+
+    msg!(
+        "Rebalancing {} liquidity from reserve {} to reserve {}",
+        rebalance_amount,
+        ctx.accounts.source_reserve.key(),
+        ctx.accounts.destination_reserve.key()
+    );
+
+    token_transfer::rebalance_reserve_liquidity_transfer(
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.reserve_liquidity_mint.to_account_info(),
+        ctx.accounts
+            .source_reserve_supply_liquidity
+            .to_account_info(),
+        ctx.accounts
+            .destination_reserve_supply_liquidity
+            .to_account_info(),
+        ctx.accounts.lending_market_authority.to_account_info(),
+        authority_signer_seeds,
+        rebalance_amount,
+        ctx.accounts.reserve_liquidity_mint.decimals,
+    )?;
+
+    lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+        token_interface::accessor::amount(
+            &ctx.accounts
+                .source_reserve_supply_liquidity
+                .to_account_info(),
+        )?,
+        source_reserve.liquidity.available_amount,
+        initial_source_vault_balance,
+        initial_source_available_liquidity,
+        LendingAction::Subtractive(rebalance_amount),
+    )?;
+
+    lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+        token_interface::accessor::amount(
+            &ctx.accounts
+                .destination_reserve_supply_liquidity
+                .to_account_info(),
+        )?,
+        destination_reserve.liquidity.available_amount,
+        initial_destination_vault_balance,
+        initial_destination_available_liquidity,
+        LendingAction::Additive(rebalance_amount),
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RebalanceReserveLiquidity<'info> {
+    pub lending_market_owner: Signer<'info>,
+
+    #[account(has_one = lending_market_owner)]
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(
+        seeds = [seeds::LENDING_MARKET_AUTH, lending_market.key().as_ref()],
+        bump = lending_market.load()?.bump_seed as u8,
+    )]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub source_reserve: AccountLoader<'info, Reserve>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub destination_reserve: AccountLoader<'info, Reserve>,
+
+    #[account(
+        address = source_reserve.load()?.liquidity.mint_pubkey,
+        mint::token_program = token_program,
+    )]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        address = source_reserve.load()?.liquidity.supply_vault,
+    )]
+    pub source_reserve_supply_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        address = destination_reserve.load()?.liquidity.supply_vault,
+    )]
+    pub destination_reserve_supply_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
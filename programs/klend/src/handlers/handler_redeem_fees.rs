@@ -28,18 +28,20 @@ pub fn process(ctx: Context<RedeemFees>) -> Result<()> {
 
     msg!("Redeeming fees: {}", withdraw_amount);
 
-    token_transfer::withdraw_fees_from_reserve(
-        ctx.accounts.token_program.to_account_info(),
-        ctx.accounts.reserve_liquidity_mint.to_account_info(),
-        ctx.accounts.reserve_supply_liquidity.to_account_info(),
-        ctx.accounts
-            .reserve_liquidity_fee_receiver
-            .to_account_info(),
-        ctx.accounts.lending_market_authority.to_account_info(),
-        authority_signer_seeds,
-        withdraw_amount,
-        ctx.accounts.reserve_liquidity_mint.decimals,
-    )?;
+    if withdraw_amount > 0 {
+        token_transfer::withdraw_fees_from_reserve(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reserve_liquidity_mint.to_account_info(),
+            ctx.accounts.reserve_supply_liquidity.to_account_info(),
+            ctx.accounts
+                .reserve_liquidity_fee_receiver
+                .to_account_info(),
+            ctx.accounts.lending_market_authority.to_account_info(),
+            authority_signer_seeds,
+            withdraw_amount,
+            ctx.accounts.reserve_liquidity_mint.decimals,
+        )?;
+    }
 
     Ok(())
 }
@@ -43,8 +43,13 @@ pub fn process(ctx: Context<RedeemReserveCollateral>, collateral_amount: u64) ->
     let initial_reserve_available_liquidity = reserve.liquidity.available_amount;
 
     lending_operations::refresh_reserve(reserve, &clock, None, lending_market.referral_fee_bps)?;
-    let withdraw_liquidity_amount =
-        lending_operations::redeem_reserve_collateral(reserve, collateral_amount, &clock, true)?;
+    let withdraw_liquidity_amount = lending_operations::redeem_reserve_collateral(
+        reserve,
+        collateral_amount,
+        &clock,
+        true,
+        true,
+    )?;
 
     msg!(
         "pnl: Redeeming reserve collateral {}",
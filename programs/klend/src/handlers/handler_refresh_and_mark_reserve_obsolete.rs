@@ -0,0 +1,105 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    fraction::FractionExtra,
+    lending_market::lending_operations,
+    state::{LendingMarket, Reserve, ReserveStatus, ReserveStatusChanged},
+    utils::{prices::get_price, DUST_LAMPORT_THRESHOLD, PROGRAM_VERSION},
+    LendingError,
+};
+
+pub fn process(ctx: Context<RefreshAndMarkReserveObsolete>, force: bool) -> Result<()> {
+    let clock = &Clock::get()?;
+    let reserve = &mut ctx.accounts.reserve.load_mut()?;
+    let lending_market = &ctx.accounts.lending_market.load()?;
+
+    require!(
+        reserve.version == PROGRAM_VERSION as u64,
+        LendingError::ReserveDeprecated
+    );
+
+    let previous_status = reserve.config.status();
+    require!(
+        previous_status != ReserveStatus::Obsolete,
+        LendingError::ReserveObsolete
+    );
+
+    reserve.config.token_info.validate_token_info_config(
+        ctx.accounts.pyth_oracle.as_ref(),
+        ctx.accounts.switchboard_price_oracle.as_ref(),
+        ctx.accounts.switchboard_twap_oracle.as_ref(),
+        ctx.accounts.scope_prices.as_ref(),
+    )?;
+
+    let price_res = get_price(
+        &reserve.config.token_info,
+        ctx.accounts.pyth_oracle.as_ref(),
+        ctx.accounts.switchboard_price_oracle.as_ref(),
+        ctx.accounts.switchboard_twap_oracle.as_ref(),
+        ctx.accounts.scope_prices.as_ref(),
+        clock.unix_timestamp,
+    )?;
+
+    lending_operations::refresh_reserve(
+        reserve,
+        clock,
+        price_res,
+        lending_market.referral_fee_bps,
+    )?;
+    lending_operations::refresh_reserve_limit_timestamps(reserve, clock.slot)?;
+
+    let available_amount = reserve.liquidity.available_amount;
+    let borrowed_amount = reserve.liquidity.total_borrow();
+    if available_amount >= DUST_LAMPORT_THRESHOLD || borrowed_amount >= DUST_LAMPORT_THRESHOLD {
+        msg!(
+            "Reserve has available amount {} and borrowed amount {}",
+            available_amount,
+            borrowed_amount.to_display()
+        );
+        if !force {
+            return err!(LendingError::ReserveNotEmpty);
+        }
+        msg!("WARNING! Forcing reserve to obsolete status");
+    }
+
+    reserve.config.status = ReserveStatus::Obsolete.into();
+
+    msg!(
+        "Reserve {} {} status changed from {:?} to {:?}",
+        ctx.accounts.reserve.key(),
+        reserve.config.token_info.symbol(),
+        previous_status,
+        ReserveStatus::Obsolete
+    );
+
+    emit!(ReserveStatusChanged {
+        reserve: ctx.accounts.reserve.key(),
+        slot: clock.slot,
+        previous_status: previous_status.into(),
+        new_status: ReserveStatus::Obsolete.into(),
+        available_amount,
+        borrowed_amount_sf: reserve.liquidity.borrowed_amount_sf,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RefreshAndMarkReserveObsolete<'info> {
+    lending_market_owner: Signer<'info>,
+
+    #[account(has_one = lending_market_owner)]
+    lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    reserve: AccountLoader<'info, Reserve>,
+
+    pub pyth_oracle: Option<AccountInfo<'info>>,
+
+    pub switchboard_price_oracle: Option<AccountInfo<'info>>,
+    pub switchboard_twap_oracle: Option<AccountInfo<'info>>,
+
+    pub scope_prices: Option<AccountInfo<'info>>,
+}
@@ -44,6 +44,8 @@ pub fn process(ctx: Context<RefreshReserve>) -> Result<()> {
         None
     };
 
+    let previous_cumulative_borrow_rate_bsf = reserve.liquidity.cumulative_borrow_rate_bsf.value;
+
     lending_operations::refresh_reserve(
         reserve,
         clock,
@@ -51,6 +53,13 @@ pub fn process(ctx: Context<RefreshReserve>) -> Result<()> {
         lending_market.referral_fee_bps,
     )?;
     lending_operations::refresh_reserve_limit_timestamps(reserve, clock.slot)?;
+    lending_operations::emit_cumulative_borrow_rate_snapshot(
+        ctx.accounts.reserve.key(),
+        reserve,
+        lending_market,
+        previous_cumulative_borrow_rate_bsf,
+        clock.slot,
+    );
 
     msg!(
         "Token: {} Price: {}",
@@ -8,7 +8,7 @@ use crate::{
     LendingError, LendingMarket,
 };
 
-fn maybe_price_account<'a, 'info>(
+pub(crate) fn maybe_price_account<'a, 'info>(
     price_account: Option<&'a AccountInfo<'info>>,
 ) -> Result<Option<&'a AccountInfo<'info>>> {
     if let Some(price_account) = price_account {
@@ -91,6 +91,9 @@ pub fn process(ctx: Context<RefreshReservesBatch>, skip_price_updates: bool) ->
             None
         };
 
+        let previous_cumulative_borrow_rate_bsf =
+            reserve.liquidity.cumulative_borrow_rate_bsf.value;
+
         lending_operations::refresh_reserve(
             reserve,
             clock,
@@ -98,6 +101,13 @@ pub fn process(ctx: Context<RefreshReservesBatch>, skip_price_updates: bool) ->
             lending_market.referral_fee_bps,
         )?;
         lending_operations::refresh_reserve_limit_timestamps(reserve, clock.slot)?;
+        lending_operations::emit_cumulative_borrow_rate_snapshot(
+            *reserve_acc.key,
+            reserve,
+            lending_market,
+            previous_cumulative_borrow_rate_bsf,
+            clock.slot,
+        );
 
         if !skip_price_updates {
             msg!(
@@ -0,0 +1,117 @@
+use anchor_lang::{prelude::*, Accounts};
+use anchor_spl::{
+    token::Token,
+    token_interface::{self, Mint, TokenAccount},
+};
+
+use crate::{
+    gen_signer_seeds,
+    lending_market::{lending_checks, lending_operations},
+    state::{LendingMarket, Reserve},
+    utils::{
+        seeds::{self, BASE_SEED_REFERRER_TOKEN_STATE},
+        token_transfer, PROGRAM_VERSION,
+    },
+    LendingAction, LendingError, ReferrerTokenState, ReserveStatus,
+};
+
+pub fn process(ctx: Context<RestakeReferrerFees>) -> Result<()> {
+    let clock = &Clock::get()?;
+
+    let reserve = &mut ctx.accounts.reserve.load_mut()?;
+    let referrer_token_state = &mut ctx.accounts.referrer_token_state.load_mut()?;
+    let lending_market = &ctx.accounts.lending_market.load()?;
+    let lending_market_key = ctx.accounts.lending_market.key();
+
+    require!(
+        reserve.config.status() != ReserveStatus::Obsolete,
+        LendingError::ReserveObsolete
+    );
+
+    require!(
+        reserve.version == PROGRAM_VERSION as u64,
+        LendingError::ReserveDeprecated
+    );
+
+    let authority_signer_seeds =
+        gen_signer_seeds!(lending_market_key, lending_market.bump_seed as u8);
+
+    let initial_reserve_token_balance = token_interface::accessor::amount(
+        &ctx.accounts.reserve_liquidity_supply.to_account_info(),
+    )?;
+    let initial_reserve_available_liquidity = reserve.liquidity.available_amount;
+
+    let restake_amount =
+        lending_operations::withdraw_referrer_fees(reserve, clock.slot, referrer_token_state)?;
+
+    lending_operations::refresh_reserve(reserve, clock, None, lending_market.referral_fee_bps)?;
+    let collateral_amount =
+        lending_operations::deposit_reserve_liquidity(reserve, clock, restake_amount)?;
+
+    msg!(
+        "Restaking referrer fees {} in reserve {:?} for {} collateral",
+        restake_amount,
+        ctx.accounts.reserve.key(),
+        collateral_amount
+    );
+
+    token_transfer::restake_referrer_fees_transfer(
+        ctx.accounts.collateral_token_program.to_account_info(),
+        ctx.accounts.reserve_collateral_mint.to_account_info(),
+        ctx.accounts.lending_market_authority.to_account_info(),
+        ctx.accounts
+            .referrer_destination_collateral
+            .to_account_info(),
+        authority_signer_seeds,
+        collateral_amount,
+    )?;
+
+    lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+        token_interface::accessor::amount(&ctx.accounts.reserve_liquidity_supply.to_account_info())
+            .unwrap(),
+        reserve.liquidity.available_amount,
+        initial_reserve_token_balance,
+        initial_reserve_available_liquidity,
+        LendingAction::Additive(0),
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RestakeReferrerFees<'info> {
+    pub referrer: Signer<'info>,
+
+    #[account(mut,
+        seeds = [BASE_SEED_REFERRER_TOKEN_STATE, referrer.key().as_ref(), reserve.key().as_ref()],
+        bump = referrer_token_state.load()?.bump.try_into().unwrap()
+    )]
+    pub referrer_token_state: AccountLoader<'info, ReferrerTokenState>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub reserve: AccountLoader<'info, Reserve>,
+
+    #[account(
+        address = reserve.load()?.liquidity.supply_vault,
+    )]
+    pub reserve_liquidity_supply: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = reserve.load()?.collateral.mint_pubkey)]
+    pub reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        token::mint = reserve_collateral_mint.key()
+    )]
+    pub referrer_destination_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+    #[account(
+        seeds = [seeds::LENDING_MARKET_AUTH, lending_market.key().as_ref()],
+        bump = lending_market.load()?.bump_seed as u8,
+    )]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    pub collateral_token_program: Program<'info, Token>,
+}
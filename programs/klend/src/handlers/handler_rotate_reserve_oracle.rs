@@ -0,0 +1,94 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    lending_market::lending_operations,
+    state::{LendingMarket, Reserve, UpdateConfigMode},
+    utils::{prices::get_price, Fraction},
+    LendingError,
+};
+
+pub fn process(ctx: Context<RotateReserveOracle>, mode: u64, value: &[u8]) -> Result<()> {
+    let mode =
+        UpdateConfigMode::try_from(mode).map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    require!(
+        matches!(
+            mode,
+            UpdateConfigMode::UpdateTokenInfoScopeChain
+                | UpdateConfigMode::UpdateScopePriceFeed
+                | UpdateConfigMode::UpdatePythPrice
+                | UpdateConfigMode::UpdateSwitchboardFeed
+                | UpdateConfigMode::UpdateSwitchboardTwapFeed
+        ),
+        LendingError::InvalidConfig
+    );
+
+    let reserve = &mut ctx.accounts.reserve.load_mut()?;
+    let market = ctx.accounts.lending_market.load()?;
+    let clock = Clock::get()?;
+
+    msg!(
+        "Rotating reserve {:?} {} oracle with mode {:?}",
+        ctx.accounts.reserve.key(),
+        reserve.config.token_info.symbol(),
+        mode,
+    );
+
+    lending_operations::refresh_reserve(reserve, &clock, None, market.referral_fee_bps)?;
+
+    let previous_price = reserve.liquidity.get_market_price_f();
+    require!(previous_price > Fraction::ZERO, LendingError::PriceNotValid);
+
+    lending_operations::update_reserve_config(reserve, mode, value);
+
+    reserve.config.token_info.validate_token_info_config(
+        ctx.accounts.pyth_oracle.as_ref(),
+        ctx.accounts.switchboard_price_oracle.as_ref(),
+        ctx.accounts.switchboard_twap_oracle.as_ref(),
+        ctx.accounts.scope_prices.as_ref(),
+    )?;
+
+    let new_price = get_price(
+        &reserve.config.token_info,
+        ctx.accounts.pyth_oracle.as_ref(),
+        ctx.accounts.switchboard_price_oracle.as_ref(),
+        ctx.accounts.switchboard_twap_oracle.as_ref(),
+        ctx.accounts.scope_prices.as_ref(),
+        clock.unix_timestamp,
+    )?
+    .ok_or_else(|| error!(LendingError::PriceNotValid))?;
+
+    lending_operations::check_oracle_rotation_price_divergence(
+        previous_price,
+        new_price.price,
+        reserve.config.max_oracle_rotation_price_divergence_bps,
+    )?;
+
+    lending_operations::utils::validate_reserve_config(
+        &reserve.config,
+        &market,
+        ctx.accounts.reserve.key(),
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RotateReserveOracle<'info> {
+    lending_market_owner: Signer<'info>,
+
+    #[account(has_one = lending_market_owner)]
+    lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    reserve: AccountLoader<'info, Reserve>,
+
+    pub pyth_oracle: Option<AccountInfo<'info>>,
+
+    pub switchboard_price_oracle: Option<AccountInfo<'info>>,
+    pub switchboard_twap_oracle: Option<AccountInfo<'info>>,
+
+    pub scope_prices: Option<AccountInfo<'info>>,
+}
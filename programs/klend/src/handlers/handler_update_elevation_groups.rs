@@ -0,0 +1,41 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    lending_market::lending_operations,
+    state::{lending_market::ElevationGroup, LendingMarket},
+    LendingError,
+};
+
+pub fn process(
+    ctx: Context<UpdateElevationGroups>,
+    elevation_groups: Vec<ElevationGroup>,
+) -> Result<()> {
+    let market = &mut ctx.accounts.lending_market.load_mut()?;
+
+    for (index, elevation_group) in elevation_groups.iter().enumerate() {
+        if elevation_groups[..index]
+            .iter()
+            .any(|previous| previous.id == elevation_group.id)
+        {
+            msg!(
+                "Elevation group {} is set more than once",
+                elevation_group.id
+            );
+            return err!(LendingError::InvalidElevationGroupConfig);
+        }
+    }
+
+    for elevation_group in elevation_groups {
+        lending_operations::update_elevation_group(market, elevation_group)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateElevationGroups<'info> {
+    lending_market_owner: Signer<'info>,
+
+    #[account(mut, has_one = lending_market_owner)]
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+}
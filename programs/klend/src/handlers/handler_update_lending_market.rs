@@ -2,11 +2,15 @@ use anchor_lang::{prelude::*, Accounts};
 
 use crate::{
     borsh::BorshDeserialize,
-    fraction::FractionExtra,
-    state::{lending_market::ElevationGroup, LendingMarket, UpdateLendingMarketMode},
-    utils::{
-        validate_numerical_bool, Fraction, ELEVATION_GROUP_NONE, FULL_BPS, MAX_NUM_ELEVATION_GROUPS,
+    lending_market::lending_operations,
+    state::{
+        lending_market::{
+            ElevationGroup, LIQUIDATION_COLLATERAL_PRIORITY_HIGHEST_VALUE,
+            OBLIGATION_VALUE_DRIFT_CHECK_STRICT,
+        },
+        LendingMarket, UpdateLendingMarketMode,
     },
+    utils::{validate_numerical_bool, Fraction, FULL_BPS, MAX_NUM_ELEVATION_GROUPS_PER_RESERVE},
     LendingError, VALUE_BYTE_MAX_ARRAY_LEN_MARKET_UPDATE,
 };
 
@@ -118,46 +122,7 @@ pub fn process(
             let elevation_group: ElevationGroup =
                 BorshDeserialize::deserialize(&mut &value[..]).unwrap();
 
-            if elevation_group.id > MAX_NUM_ELEVATION_GROUPS {
-                return err!(LendingError::InvalidElevationGroupConfig);
-            }
-
-            if elevation_group.id != ELEVATION_GROUP_NONE
-                && elevation_group.liquidation_threshold_pct == 0
-            {
-                return err!(LendingError::InvalidElevationGroupConfig);
-            }
-
-            if elevation_group.liquidation_threshold_pct >= 100
-                || elevation_group.ltv_pct >= 100
-                || elevation_group.ltv_pct > elevation_group.liquidation_threshold_pct
-                || elevation_group.max_liquidation_bonus_bps > FULL_BPS
-            {
-                return err!(LendingError::InvalidElevationGroupConfig);
-            }
-
-            if elevation_group.id != ELEVATION_GROUP_NONE
-                && (elevation_group.debt_reserve == Pubkey::default()
-                    || elevation_group.max_reserves_as_collateral == 0)
-            {
-                return err!(LendingError::InvalidElevationGroupConfig);
-            }
-
-            if Fraction::from_percent(elevation_group.liquidation_threshold_pct)
-                + Fraction::from_percent(elevation_group.liquidation_threshold_pct)
-                    * Fraction::from_bps(elevation_group.max_liquidation_bonus_bps)
-                > Fraction::ONE
-            {
-                msg!("Max liquidation bonus * liquidation threshold is greater than 100%, invalid");
-                return err!(LendingError::InvalidElevationGroupConfig);
-            }
-
-            let prev_elevation_group = market.get_elevation_group(elevation_group.id);
-
-            msg!("Prev value is {:?}", prev_elevation_group);
-            msg!("New value is {:?}", elevation_group);
-
-            market.set_elevation_group(elevation_group)?;
+            lending_operations::update_elevation_group(market, elevation_group)?;
         }
         UpdateLendingMarketMode::UpdateReferralFeeBps => {
             let value = u16::from_le_bytes(value[..2].try_into().unwrap());
@@ -249,6 +214,163 @@ pub fn process(
             msg!("New Value is {}", name.trim_end_matches('\0'));
             market.name.copy_from_slice(name_bytes);
         }
+        UpdateLendingMarketMode::UpdateMaxPriceAgeDivergenceSecs => {
+            let value = u64::from_le_bytes(value[..8].try_into().unwrap());
+            msg!("Prev Value is {}", market.max_price_age_divergence_secs);
+            msg!("New Value is {}", value);
+            market.max_price_age_divergence_secs = value;
+        }
+        UpdateLendingMarketMode::UpdateCumulativeBorrowRateEventsEnabled => {
+            let cumulative_borrow_rate_events_enabled = value[0];
+            msg!(
+                "Prev Value is {:?}",
+                market.cumulative_borrow_rate_events_enabled
+            );
+            msg!("New Value is {:?}", cumulative_borrow_rate_events_enabled);
+            validate_numerical_bool(cumulative_borrow_rate_events_enabled)?;
+            market.cumulative_borrow_rate_events_enabled = cumulative_borrow_rate_events_enabled;
+        }
+        UpdateLendingMarketMode::UpdateMaxObsoleteReservesPerObligation => {
+            let max_obsolete_reserves_per_obligation = value[0];
+            msg!(
+                "Prev Value is {:?}",
+                market.max_obsolete_reserves_per_obligation
+            );
+            msg!("New Value is {:?}", max_obsolete_reserves_per_obligation);
+            market.max_obsolete_reserves_per_obligation = max_obsolete_reserves_per_obligation;
+        }
+        UpdateLendingMarketMode::UpdateMinLiquidationRepayValue => {
+            let value = u64::from_le_bytes(value[..8].try_into().unwrap());
+            msg!("Prev Value is {:?}", market.min_liquidation_repay_value);
+            msg!("New Value is {:?}", value);
+            market.min_liquidation_repay_value = value;
+        }
+        UpdateLendingMarketMode::UpdateLiquidationBonusBoostBps => {
+            let value = u16::from_le_bytes(value[..2].try_into().unwrap());
+            msg!("Prev Value is {:?}", market.liquidation_bonus_boost_bps);
+            msg!("New Value is {:?}", value);
+            market.liquidation_bonus_boost_bps = value;
+        }
+        UpdateLendingMarketMode::UpdateMinSlotsBetweenLiquidations => {
+            let value = u64::from_le_bytes(value[..8].try_into().unwrap());
+            msg!("Prev Value is {:?}", market.min_slots_between_liquidations);
+            msg!("New Value is {:?}", value);
+            market.min_slots_between_liquidations = value;
+        }
+        UpdateLendingMarketMode::UpdateZeroLtvCollateralWithdrawalFirst => {
+            let zero_ltv_collateral_withdrawal_first = value[0];
+            msg!(
+                "Prev Value is {:?}",
+                market.zero_ltv_collateral_withdrawal_first
+            );
+            msg!("New Value is {:?}", zero_ltv_collateral_withdrawal_first);
+            validate_numerical_bool(zero_ltv_collateral_withdrawal_first)?;
+            market.zero_ltv_collateral_withdrawal_first = zero_ltv_collateral_withdrawal_first;
+        }
+        UpdateLendingMarketMode::UpdateMaxLiquidationValuePerIx => {
+            let value = u64::from_le_bytes(value[..8].try_into().unwrap());
+            msg!("Prev Value is {:?}", market.max_liquidation_value_per_ix);
+            msg!("New Value is {:?}", value);
+            market.max_liquidation_value_per_ix = value;
+        }
+        UpdateLendingMarketMode::UpdateMinElevationGroupLtvSpreadPct => {
+            let value = value[0];
+            msg!(
+                "Prev Value is {:?}",
+                market.min_elevation_group_ltv_spread_pct
+            );
+            msg!("New Value is {:?}", value);
+            if value >= 100 {
+                msg!("Min elevation group LTV spread pct must be in range [0, 100)");
+                return err!(LendingError::InvalidConfig);
+            }
+            market.min_elevation_group_ltv_spread_pct = value;
+        }
+        UpdateLendingMarketMode::UpdateStrictBorrowPriceChecks => {
+            let strict_borrow_price_checks = value[0];
+            msg!("Prev Value is {:?}", market.strict_borrow_price_checks);
+            msg!("New Value is {:?}", strict_borrow_price_checks);
+            validate_numerical_bool(strict_borrow_price_checks)?;
+            market.strict_borrow_price_checks = strict_borrow_price_checks;
+        }
+        UpdateLendingMarketMode::UpdateObligationValueDriftCheckMode => {
+            let value = value[0];
+            msg!(
+                "Prev Value is {:?}",
+                market.obligation_value_drift_check_mode
+            );
+            msg!("New Value is {:?}", value);
+            if value > OBLIGATION_VALUE_DRIFT_CHECK_STRICT {
+                msg!("Obligation value drift check mode must be 0 (off), 1 (warn) or 2 (strict)");
+                return err!(LendingError::InvalidConfig);
+            }
+            market.obligation_value_drift_check_mode = value;
+        }
+        UpdateLendingMarketMode::UpdateObligationValueDriftToleranceBps => {
+            let value = u64::from_le_bytes(value[..8].try_into().unwrap());
+            msg!(
+                "Prev Value is {:?}",
+                market.obligation_value_drift_tolerance_bps
+            );
+            msg!("New Value is {:?}", value);
+            market.obligation_value_drift_tolerance_bps = value;
+        }
+        UpdateLendingMarketMode::UpdateMinBorrowHealthBufferBps => {
+            let value = u64::from_le_bytes(value[..8].try_into().unwrap());
+            msg!("Prev Value is {:?}", market.min_borrow_health_buffer_bps);
+            msg!("New Value is {:?}", value);
+            if value > u64::from(FULL_BPS) {
+                msg!("Min borrow health buffer must be in range [0, 10000] bps");
+                return err!(LendingError::InvalidConfig);
+            }
+            market.min_borrow_health_buffer_bps = value;
+        }
+        UpdateLendingMarketMode::UpdateMinProtocolTakeRatePct => {
+            let value = value[0];
+            msg!("Prev Value is {:?}", market.min_protocol_take_rate_pct);
+            msg!("New Value is {:?}", value);
+            if value > market.protocol_take_rate_pct_bounds().1 {
+                msg!("Min protocol take rate must be in range [0, max protocol take rate]");
+                return err!(LendingError::InvalidConfig);
+            }
+            market.min_protocol_take_rate_pct = value;
+        }
+        UpdateLendingMarketMode::UpdateMaxProtocolTakeRatePct => {
+            let value = value[0];
+            msg!("Prev Value is {:?}", market.max_protocol_take_rate_pct);
+            msg!("New Value is {:?}", value);
+            if value > 100 || (value > 0 && value < market.min_protocol_take_rate_pct) {
+                msg!("Max protocol take rate must be 0 (no ceiling) or in range [min protocol take rate, 100]");
+                return err!(LendingError::InvalidConfig);
+            }
+            market.max_protocol_take_rate_pct = value;
+        }
+        UpdateLendingMarketMode::UpdateLiquidationCollateralPriority => {
+            let value = value[0];
+            msg!("Prev Value is {:?}", market.liquidation_collateral_priority);
+            msg!("New Value is {:?}", value);
+            if value > LIQUIDATION_COLLATERAL_PRIORITY_HIGHEST_VALUE {
+                msg!("Liquidation collateral priority must be 0 (lowest liquidation LTV) or 1 (highest value)");
+                return err!(LendingError::InvalidConfig);
+            }
+            market.liquidation_collateral_priority = value;
+        }
+        UpdateLendingMarketMode::UpdateMaxElevationGroupsPerReserve => {
+            let value = value[0];
+            msg!(
+                "Prev Value is {:?}",
+                market.max_elevation_groups_per_reserve
+            );
+            msg!("New Value is {:?}", value);
+            if value > MAX_NUM_ELEVATION_GROUPS_PER_RESERVE {
+                msg!(
+                    "Max elevation groups per reserve must be in range [0, {}]",
+                    MAX_NUM_ELEVATION_GROUPS_PER_RESERVE
+                );
+                return err!(LendingError::InvalidConfig);
+            }
+            market.max_elevation_groups_per_reserve = value;
+        }
     }
 
     Ok(())
@@ -0,0 +1,38 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    lending_market::lending_operations,
+    state::{obligation::Obligation, LendingMarket},
+};
+
+pub fn process(
+    ctx: Context<UpdateObligationBorrowFactorDiscountWhitelist>,
+    whitelisted: bool,
+) -> Result<()> {
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+
+    msg!(
+        "Updating obligation {} borrow factor discount whitelist",
+        ctx.accounts.obligation.key()
+    );
+
+    lending_operations::update_obligation_borrow_factor_discount_whitelist(
+        obligation,
+        whitelisted,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateObligationBorrowFactorDiscountWhitelist<'info> {
+    pub lending_market_owner: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    #[account(has_one = lending_market_owner)]
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+}
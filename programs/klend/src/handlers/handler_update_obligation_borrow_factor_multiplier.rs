@@ -0,0 +1,40 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    lending_market::lending_operations,
+    state::{obligation::Obligation, LendingMarket},
+};
+
+pub fn process(
+    ctx: Context<UpdateObligationBorrowFactorMultiplier>,
+    borrow_factor_multiplier_bps: u64,
+) -> Result<()> {
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+    let clock = Clock::get()?;
+
+    msg!(
+        "Updating obligation {} borrow factor multiplier",
+        ctx.accounts.obligation.key()
+    );
+
+    lending_operations::update_obligation_borrow_factor_multiplier(
+        obligation,
+        borrow_factor_multiplier_bps,
+        clock.slot,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateObligationBorrowFactorMultiplier<'info> {
+    pub risk_council: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    #[account(has_one = risk_council)]
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{lending_market::lending_operations, LendingMarket, Obligation, Reserve};
+
+pub fn process(ctx: Context<UpdateObligationCollateralProtected>, protected: bool) -> Result<()> {
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+
+    lending_operations::update_obligation_collateral_protected(
+        obligation,
+        ctx.accounts.deposit_reserve.key(),
+        protected,
+    )?;
+
+    msg!(
+        "Set protected {} for obligation {} deposit in reserve {}",
+        protected,
+        ctx.accounts.obligation.key(),
+        ctx.accounts.deposit_reserve.key()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateObligationCollateralProtected<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = owner
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(
+        has_one = lending_market
+    )]
+    pub deposit_reserve: AccountLoader<'info, Reserve>,
+}
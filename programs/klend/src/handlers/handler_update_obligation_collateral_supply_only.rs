@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::{lending_market::lending_operations, LendingMarket, Obligation, Reserve};
+
+pub fn process(
+    ctx: Context<UpdateObligationCollateralSupplyOnly>,
+    supply_only: bool,
+) -> Result<()> {
+    let lending_market = &ctx.accounts.lending_market.load()?;
+    let deposit_reserve = &ctx.accounts.deposit_reserve.load()?;
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+    let clock = Clock::get()?;
+
+    lending_operations::update_obligation_collateral_supply_only(
+        lending_market,
+        deposit_reserve,
+        obligation,
+        ctx.accounts.deposit_reserve.key(),
+        supply_only,
+        clock.slot,
+    )?;
+
+    msg!(
+        "Set supply-only {} for obligation {} deposit in reserve {}",
+        supply_only,
+        ctx.accounts.obligation.key(),
+        ctx.accounts.deposit_reserve.key()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateObligationCollateralSupplyOnly<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = owner
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(
+        has_one = lending_market
+    )]
+    pub deposit_reserve: AccountLoader<'info, Reserve>,
+}
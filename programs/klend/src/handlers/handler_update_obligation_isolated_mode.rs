@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{LendingError, LendingMarket, Obligation};
+
+pub fn process(ctx: Context<UpdateObligationIsolatedMode>, isolated_mode: u8) -> Result<()> {
+    require!(isolated_mode <= 1, LendingError::InvalidFlag);
+
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+
+    if isolated_mode == 1 && (obligation.deposits_count() > 1 || obligation.borrows_count() > 1) {
+        msg!(
+            "Obligation has {} deposits and {} borrows, cannot enable isolated mode",
+            obligation.deposits_count(),
+            obligation.borrows_count()
+        );
+        return err!(LendingError::ObligationIsolatedModeViolation);
+    }
+
+    msg!(
+        "Updating obligation {} isolated mode from {} to {}",
+        ctx.accounts.obligation.key(),
+        obligation.isolated_mode,
+        isolated_mode
+    );
+
+    obligation.isolated_mode = isolated_mode;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateObligationIsolatedMode<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = owner
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{LendingMarket, Obligation};
+
+pub fn process(
+    ctx: Context<UpdateObligationSelfBorrowLimit>,
+    self_borrow_limit_value: u64,
+) -> Result<()> {
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+
+    msg!(
+        "Updating obligation {} self borrow limit value from {} to {}",
+        ctx.accounts.obligation.key(),
+        obligation.self_borrow_limit_value,
+        self_borrow_limit_value
+    );
+
+    obligation.self_borrow_limit_value = self_borrow_limit_value;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateObligationSelfBorrowLimit<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = owner
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+}
@@ -1,9 +1,10 @@
 use anchor_lang::{prelude::*, Accounts};
 
 use crate::{
-    lending_market::lending_operations,
+    lending_market::{farms_ixs, lending_operations},
     state::{LendingMarket, Reserve, UpdateConfigMode},
-    LendingError,
+    utils::{seeds, MIN_DELEVERAGING_MARGIN_CALL_PERIOD_SECS},
+    LendingError, ReserveFarmKind,
 };
 
 pub fn process(
@@ -29,13 +30,59 @@ pub fn process(
     let clock = Clock::get()?;
     lending_operations::refresh_reserve(reserve, &clock, None, market.referral_fee_bps)?;
 
+    let previous_token_info = reserve.config.token_info;
+    let previous_supply_referrer = reserve.config.supply_referrer;
+    let previous_host_fee_receiver = reserve.config.host_fee_receiver;
+    let previous_deleveraging_margin_call_period_secs =
+        reserve.config.deleveraging_margin_call_period_secs;
+
     lending_operations::update_reserve_config(reserve, mode, value);
 
+    if reserve.liquidity.market_price_sf != 0
+        && !reserve
+            .config
+            .token_info
+            .has_same_oracle_feeds(&previous_token_info)
+    {
+        msg!("Oracle feeds of a priced reserve can only be changed with rotate_reserve_oracle");
+        return err!(LendingError::InvalidConfig);
+    }
+
+    if reserve.config.supply_referrer != previous_supply_referrer
+        && reserve.liquidity.pending_supply_referrer_fees_sf != 0
+    {
+        msg!("Pending supply referrer fees must be claimed before changing the supply referrer");
+        return err!(LendingError::InvalidConfig);
+    }
+
+    if reserve.config.deleveraging_margin_call_period_secs
+        != previous_deleveraging_margin_call_period_secs
+        && reserve.config.deleveraging_margin_call_period_secs
+            < MIN_DELEVERAGING_MARGIN_CALL_PERIOD_SECS
+    {
+        msg!(
+            "Deleveraging margin call period must be at least {} seconds",
+            MIN_DELEVERAGING_MARGIN_CALL_PERIOD_SECS
+        );
+        return err!(LendingError::InvalidConfig);
+    }
+
+    if reserve.config.host_fee_receiver != previous_host_fee_receiver
+        && reserve.liquidity.accumulated_host_fees_sf != 0
+    {
+        msg!("Accumulated host fees must be withdrawn before changing the host fee receiver");
+        return err!(LendingError::InvalidConfig);
+    }
+
     if skip_validation {
         require!(
             !matches!(
                 mode,
-                UpdateConfigMode::UpdateDepositLimit | UpdateConfigMode::UpdateBorrowLimit
+                UpdateConfigMode::UpdateDepositLimit
+                    | UpdateConfigMode::UpdateBorrowLimit
+                    | UpdateConfigMode::UpdateLimitsInQuoteValue
+                    | UpdateConfigMode::UpdateTokenInfoQuoteCurrency
+                    | UpdateConfigMode::UpdateProtocolTakeRate
             ),
             LendingError::InvalidConfig
         );
@@ -48,6 +95,31 @@ pub fn process(
         )?;
     }
 
+    let farm_kind = match mode {
+        UpdateConfigMode::UpdateFarmCollateral => Some(ReserveFarmKind::Collateral),
+        UpdateConfigMode::UpdateFarmDebt => Some(ReserveFarmKind::Debt),
+        _ => None,
+    };
+    if let Some(farm_kind) = farm_kind {
+        let farm = reserve.get_farm(farm_kind);
+        if farm != Pubkey::default() {
+            let farm_state = ctx
+                .remaining_accounts
+                .first()
+                .filter(|farm_state| *farm_state.key == farm)
+                .ok_or_else(|| {
+                    msg!("Farm state {} must be passed as remaining account", farm);
+                    error!(LendingError::InvalidAccountInput)
+                })?;
+            farms_ixs::validate_reserve_farm_state(
+                farm_state,
+                reserve,
+                &seeds::pda::lending_market_auth(&ctx.accounts.lending_market.key()),
+                farm_kind,
+            )?;
+        }
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,41 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    fraction::FractionExtra,
+    lending_market::lending_operations,
+    state::{LendingMarket, Reserve},
+};
+
+pub fn process(ctx: Context<UpdateReserveDeprecation>, deprecate: bool) -> Result<()> {
+    let reserve = &mut ctx.accounts.reserve.load_mut()?;
+
+    lending_operations::update_reserve_deprecation(reserve, deprecate)?;
+
+    msg!(
+        "Reserve {} {} {} by the lending market owner, available amount {}, borrowed amount {}",
+        ctx.accounts.reserve.key(),
+        reserve.config.token_info.symbol(),
+        if deprecate {
+            "deprecated"
+        } else {
+            "reactivated"
+        },
+        reserve.liquidity.available_amount,
+        reserve.liquidity.total_borrow().to_display()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateReserveDeprecation<'info> {
+    pub lending_market_owner: Signer<'info>,
+
+    #[account(has_one = lending_market_owner)]
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub reserve: AccountLoader<'info, Reserve>,
+}
@@ -0,0 +1,36 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    borsh::BorshDeserialize,
+    lending_market::lending_operations,
+    state::{LendingMarket, Reserve, ReserveConfig},
+};
+
+pub fn process(ctx: Context<ValidateReserveConfig>, config: &[u8]) -> Result<()> {
+    let config = ReserveConfig::deserialize(&mut &config[..])
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let market = ctx.accounts.lending_market.load()?;
+
+    msg!(
+        "Validating candidate config for reserve {:?}",
+        ctx.accounts.reserve.key()
+    );
+
+    lending_operations::utils::validate_reserve_config(
+        &config,
+        &market,
+        ctx.accounts.reserve.key(),
+    )?;
+
+    msg!("Reserve config is valid");
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ValidateReserveConfig<'info> {
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(has_one = lending_market)]
+    pub reserve: AccountLoader<'info, Reserve>,
+}
@@ -11,12 +11,14 @@ use crate::{
     lending_market::{lending_checks, lending_operations},
     state::{obligation::Obligation, LendingMarket, Reserve},
     utils::{close_account_loader, seeds, token_transfer},
-    LendingAction, ReserveFarmKind, WithdrawObligationCollateralAndRedeemReserveCollateralAccounts,
+    LendingAction, LendingError, ReserveFarmKind,
+    WithdrawObligationCollateralAndRedeemReserveCollateralAccounts,
 };
 
 pub fn process(
     ctx: Context<WithdrawObligationCollateralAndRedeemReserveCollateral>,
     collateral_amount: u64,
+    min_liquidity_received: Option<u64>,
 ) -> Result<()> {
     let close_obligation = {
         check_refresh_ixs!(ctx, withdraw_reserve, ReserveFarmKind::Collateral);
@@ -56,6 +58,7 @@ pub fn process(
             withdraw_obligation_amount,
             clock,
             true,
+            true,
         )?;
         msg!(
             "pnl: Withdraw obligation collateral {} and redeem reserve collateral {}",
@@ -63,6 +66,17 @@ pub fn process(
             withdraw_liquidity_amount
         );
 
+        if let Some(min_liquidity_received) = min_liquidity_received {
+            if withdraw_liquidity_amount < min_liquidity_received {
+                msg!(
+                    "Redeemed liquidity {} is below the minimum expected {}",
+                    withdraw_liquidity_amount,
+                    min_liquidity_received
+                );
+                return err!(LendingError::RedeemedLiquidityTooSmall);
+            }
+        }
+
         token_transfer::withdraw_and_redeem_reserve_collateral_transfer(
             ctx.accounts.collateral_token_program.to_account_info(),
             ctx.accounts.liquidity_token_program.to_account_info(),
@@ -0,0 +1,123 @@
+use anchor_lang::{prelude::*, Accounts};
+use anchor_spl::{token::spl_token, token_2022::spl_token_2022, token_interface};
+
+use crate::{
+    gen_signer_seeds,
+    lending_market::lending_operations,
+    state::{LendingMarket, Reserve},
+    utils::{constraints, seeds, token_transfer, FatAccountLoader},
+    LendingError,
+};
+
+/// Each reserve is passed as a writable remaining account, followed by its liquidity mint, its
+/// supply vault, the lending market owner's token account and the token program. Reserves with no
+/// redeemable protocol fees are skipped.
+pub fn process<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawProtocolFeesBatch<'info>>,
+) -> Result<()> {
+    let clock = &Clock::get()?;
+    let market = ctx.accounts.lending_market.load()?;
+    let lending_market_key = ctx.accounts.lending_market.key();
+    let authority_signer_seeds = gen_signer_seeds!(lending_market_key, market.bump_seed as u8);
+
+    let mut remaining_accounts_it = ctx.remaining_accounts.iter();
+    loop {
+        let Some(reserve_acc) = remaining_accounts_it.next() else {
+            break;
+        };
+        let (
+            Some(reserve_liquidity_mint),
+            Some(reserve_supply_liquidity),
+            Some(lending_market_owner_ata),
+            Some(token_program),
+        ) = (
+            remaining_accounts_it.next(),
+            remaining_accounts_it.next(),
+            remaining_accounts_it.next(),
+            remaining_accounts_it.next(),
+        )
+        else {
+            msg!("Missing accounts for reserve {}", reserve_acc.key);
+            return err!(LendingError::InvalidAccountInput);
+        };
+
+        let reserve_loader = FatAccountLoader::<Reserve>::try_from(reserve_acc)?;
+        let reserve = &mut reserve_loader.load_mut()?;
+
+        require_keys_eq!(
+            reserve.lending_market,
+            lending_market_key,
+            LendingError::InvalidAccountInput
+        );
+        require_keys_eq!(
+            reserve.liquidity.mint_pubkey,
+            *reserve_liquidity_mint.key,
+            LendingError::InvalidAccountInput
+        );
+        require_keys_eq!(
+            reserve.liquidity.supply_vault,
+            *reserve_supply_liquidity.key,
+            LendingError::InvalidAccountInput
+        );
+        require!(
+            *token_program.key == spl_token::id() || *token_program.key == spl_token_2022::id(),
+            LendingError::InvalidAccountInput
+        );
+        require_keys_eq!(
+            *reserve_liquidity_mint.owner,
+            *token_program.key,
+            LendingError::InvalidAccountInput
+        );
+
+        require_keys_eq!(
+            token_interface::accessor::mint(lending_market_owner_ata)?,
+            reserve.liquidity.mint_pubkey,
+            LendingError::InvalidAccountInput
+        );
+
+        constraints::token_2022::validate_liquidity_token_extensions(
+            reserve_liquidity_mint,
+            reserve_supply_liquidity,
+        )?;
+
+        if reserve.calculate_redeem_fees()? == 0 {
+            msg!("Nothing to withdraw from reserve {}", reserve_acc.key);
+            continue;
+        }
+
+        let amount = lending_operations::redeem_fees(reserve, clock.slot)?;
+
+        msg!(
+            "Withdrawing fees: {} from reserve {}",
+            amount,
+            reserve_acc.key
+        );
+
+        token_transfer::withdraw_fees_from_reserve(
+            token_program.clone(),
+            reserve_liquidity_mint.clone(),
+            reserve_supply_liquidity.clone(),
+            lending_market_owner_ata.clone(),
+            ctx.accounts.lending_market_authority.to_account_info(),
+            authority_signer_seeds,
+            amount,
+            reserve.liquidity.mint_decimals.try_into().unwrap(),
+        )?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFeesBatch<'info> {
+    pub lending_market_owner: Signer<'info>,
+
+    #[account(has_one = lending_market_owner)]
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(
+        seeds = [seeds::LENDING_MARKET_AUTH, lending_market.key().as_ref()],
+        bump = lending_market.load()?.bump_seed as u8,
+    )]
+    pub lending_market_authority: AccountInfo<'info>,
+}
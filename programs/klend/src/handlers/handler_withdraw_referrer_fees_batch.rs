@@ -0,0 +1,124 @@
+use anchor_lang::{prelude::*, Accounts};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    gen_signer_seeds,
+    lending_market::{lending_checks::validate_referrer_token_state, lending_operations},
+    state::{LendingMarket, Reserve},
+    utils::{constraints, seeds, token_transfer, FatAccountLoader},
+    LendingError, ReferrerTokenState,
+};
+
+pub fn process<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawReferrerFeesBatch<'info>>,
+) -> Result<()> {
+    constraints::token_2022::validate_liquidity_token_extensions(
+        &ctx.accounts.reserve_liquidity_mint.to_account_info(),
+        &ctx.accounts.referrer_token_account.to_account_info(),
+    )?;
+
+    let clock = &Clock::get()?;
+    let lending_market = &ctx.accounts.lending_market.load()?;
+    let lending_market_key = ctx.accounts.lending_market.key();
+    let mint_key = ctx.accounts.reserve_liquidity_mint.key();
+    let referrer_key = ctx.accounts.referrer.key();
+
+    let authority_signer_seeds =
+        gen_signer_seeds!(lending_market_key, lending_market.bump_seed as u8);
+
+    let mut remaining_accounts_it = ctx.remaining_accounts.iter();
+    let mut total_withdraw_amount = 0_u64;
+    loop {
+        let Some(reserve_acc) = remaining_accounts_it.next() else {
+            break;
+        };
+        let (Some(referrer_token_state_acc), Some(reserve_supply_liquidity)) =
+            (remaining_accounts_it.next(), remaining_accounts_it.next())
+        else {
+            msg!("Missing accounts for reserve {}", reserve_acc.key);
+            return err!(LendingError::InvalidAccountInput);
+        };
+
+        let reserve_loader = FatAccountLoader::<Reserve>::try_from(reserve_acc)?;
+        let referrer_token_state_loader =
+            FatAccountLoader::<ReferrerTokenState>::try_from(referrer_token_state_acc)?;
+        let reserve = &mut reserve_loader.load_mut()?;
+        let referrer_token_state = &mut referrer_token_state_loader.load_mut()?;
+
+        require_keys_eq!(
+            reserve.lending_market,
+            lending_market_key,
+            LendingError::InvalidAccountInput
+        );
+        require_keys_eq!(
+            reserve.liquidity.mint_pubkey,
+            mint_key,
+            LendingError::InvalidAccountInput
+        );
+        require_keys_eq!(
+            reserve.liquidity.supply_vault,
+            *reserve_supply_liquidity.key,
+            LendingError::InvalidAccountInput
+        );
+
+        validate_referrer_token_state(
+            referrer_token_state,
+            *referrer_token_state_acc.key,
+            mint_key,
+            referrer_key,
+            *reserve_acc.key,
+        )?;
+
+        if reserve.get_withdraw_referrer_fees(referrer_token_state)? == 0 {
+            msg!("Nothing to withdraw from reserve {}", reserve_acc.key);
+            continue;
+        }
+
+        let withdraw_amount =
+            lending_operations::withdraw_referrer_fees(reserve, clock.slot, referrer_token_state)?;
+
+        msg!(
+            "Withdrawing referrer fees: {} from reserve {}",
+            withdraw_amount,
+            reserve_acc.key
+        );
+
+        token_transfer::withdraw_fees_from_reserve(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reserve_liquidity_mint.to_account_info(),
+            reserve_supply_liquidity.clone(),
+            ctx.accounts.referrer_token_account.to_account_info(),
+            ctx.accounts.lending_market_authority.to_account_info(),
+            authority_signer_seeds,
+            withdraw_amount,
+            ctx.accounts.reserve_liquidity_mint.decimals,
+        )?;
+
+        total_withdraw_amount += withdraw_amount;
+    }
+
+    msg!("Withdrew referrer fees: {} in total", total_withdraw_amount);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawReferrerFeesBatch<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub referrer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+    #[account(
+        seeds = [seeds::LENDING_MARKET_AUTH, lending_market.key().as_ref()],
+        bump = lending_market.load()?.bump_seed as u8,
+    )]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
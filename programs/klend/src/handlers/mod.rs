@@ -1,65 +1,111 @@
 pub mod handler_borrow_obligation_liquidity;
+pub mod handler_claim_supply_referrer_fees;
+pub mod handler_compound_reserve_collateral_yield;
 pub mod handler_delete_referrer_state_and_short_url;
+pub mod handler_deposit_and_borrow;
 pub mod handler_deposit_obligation_collateral;
 pub mod handler_deposit_reserve_liquidity;
 pub mod handler_deposit_reserve_liquidity_and_obligation_collateral;
 pub mod handler_flash_borrow_reserve_liquidity;
 pub mod handler_flash_repay_reserve_liquidity;
+pub mod handler_get_obligation_snapshot;
+pub mod handler_get_reserve_vault_balance_snapshot;
 pub mod handler_init_farms_for_reserve;
 pub mod handler_init_lending_market;
 pub mod handler_init_obligation;
 pub mod handler_init_obligation_farms_for_reserve;
+pub mod handler_init_obligation_farms_for_reserves_batch;
 pub mod handler_init_referrer_state_and_short_url;
 pub mod handler_init_referrer_token_state;
 pub mod handler_init_reserve;
 pub mod handler_init_user_metadata;
 pub mod handler_liquidate_obligation_and_redeem_reserve_collateral;
+pub mod handler_migrate_obligation_seeds;
+pub mod handler_rebalance_reserve_liquidity;
 pub mod handler_redeem_fees;
 pub mod handler_redeem_reserve_collateral;
+pub mod handler_refresh_and_mark_reserve_obsolete;
 pub mod handler_refresh_obligation;
 pub mod handler_refresh_obligation_farms_for_reserve;
 pub mod handler_refresh_reserve;
 pub mod handler_refresh_reserves_batch;
 pub mod handler_repay_obligation_liquidity;
 pub mod handler_request_elevation_group;
+pub mod handler_restake_referrer_fees;
+pub mod handler_rotate_reserve_oracle;
 pub mod handler_socialize_loss;
+pub mod handler_update_elevation_groups;
 pub mod handler_update_lending_market;
 pub mod handler_update_lending_market_owner;
+pub mod handler_update_obligation_borrow_factor_discount_whitelist;
+pub mod handler_update_obligation_borrow_factor_multiplier;
+pub mod handler_update_obligation_collateral_protected;
+pub mod handler_update_obligation_collateral_supply_only;
+pub mod handler_update_obligation_isolated_mode;
+pub mod handler_update_obligation_self_borrow_limit;
 pub mod handler_update_reserve_config;
+pub mod handler_update_reserve_deprecation;
+pub mod handler_validate_reserve_config;
+pub mod handler_withdraw_host_fees;
 pub mod handler_withdraw_obligation_collateral;
 pub mod handler_withdraw_obligation_collateral_and_redeem_reserve_collateral;
 pub mod handler_withdraw_protocol_fees;
+pub mod handler_withdraw_protocol_fees_batch;
 pub mod handler_withdraw_referrer_fees;
+pub mod handler_withdraw_referrer_fees_batch;
 
 pub use handler_borrow_obligation_liquidity::*;
+pub use handler_claim_supply_referrer_fees::*;
+pub use handler_compound_reserve_collateral_yield::*;
 pub use handler_delete_referrer_state_and_short_url::*;
+pub use handler_deposit_and_borrow::*;
 pub use handler_deposit_obligation_collateral::*;
 pub use handler_deposit_reserve_liquidity::*;
 pub use handler_deposit_reserve_liquidity_and_obligation_collateral::*;
 pub use handler_flash_borrow_reserve_liquidity::*;
 pub use handler_flash_repay_reserve_liquidity::*;
+pub use handler_get_obligation_snapshot::*;
+pub use handler_get_reserve_vault_balance_snapshot::*;
 pub use handler_init_farms_for_reserve::*;
 pub use handler_init_lending_market::*;
 pub use handler_init_obligation::*;
 pub use handler_init_obligation_farms_for_reserve::*;
+pub use handler_init_obligation_farms_for_reserves_batch::*;
 pub use handler_init_referrer_state_and_short_url::*;
 pub use handler_init_referrer_token_state::*;
 pub use handler_init_reserve::*;
 pub use handler_init_user_metadata::*;
 pub use handler_liquidate_obligation_and_redeem_reserve_collateral::*;
+pub use handler_migrate_obligation_seeds::*;
+pub use handler_rebalance_reserve_liquidity::*;
 pub use handler_redeem_fees::*;
 pub use handler_redeem_reserve_collateral::*;
+pub use handler_refresh_and_mark_reserve_obsolete::*;
 pub use handler_refresh_obligation::*;
 pub use handler_refresh_obligation_farms_for_reserve::*;
 pub use handler_refresh_reserve::*;
 pub use handler_refresh_reserves_batch::*;
 pub use handler_repay_obligation_liquidity::*;
 pub use handler_request_elevation_group::*;
+pub use handler_restake_referrer_fees::*;
+pub use handler_rotate_reserve_oracle::*;
 pub use handler_socialize_loss::*;
+pub use handler_update_elevation_groups::*;
 pub use handler_update_lending_market::*;
 pub use handler_update_lending_market_owner::*;
+pub use handler_update_obligation_borrow_factor_discount_whitelist::*;
+pub use handler_update_obligation_borrow_factor_multiplier::*;
+pub use handler_update_obligation_collateral_protected::*;
+pub use handler_update_obligation_collateral_supply_only::*;
+pub use handler_update_obligation_isolated_mode::*;
+pub use handler_update_obligation_self_borrow_limit::*;
 pub use handler_update_reserve_config::*;
+pub use handler_update_reserve_deprecation::*;
+pub use handler_validate_reserve_config::*;
+pub use handler_withdraw_host_fees::*;
 pub use handler_withdraw_obligation_collateral::*;
 pub use handler_withdraw_obligation_collateral_and_redeem_reserve_collateral::*;
 pub use handler_withdraw_protocol_fees::*;
+pub use handler_withdraw_protocol_fees_batch::*;
 pub use handler_withdraw_referrer_fees::*;
+pub use handler_withdraw_referrer_fees_batch::*;
@@ -9,11 +9,48 @@ use crate::{
     handlers::{
         handler_init_farms_for_reserve::InitFarmsForReserve,
         handler_init_obligation_farms_for_reserve::InitObligationFarmsForReserve,
+        handler_init_obligation_farms_for_reserves_batch::InitObligationFarmsForReservesBatch,
+        handler_migrate_obligation_seeds::MigrateObligationSeeds,
         handler_refresh_obligation_farms_for_reserve::RefreshObligationFarmsForReserve,
     },
-    Reserve, ReserveFarmKind,
+    LendingError, Reserve, ReserveFarmKind,
 };
 
+pub fn validate_reserve_farm_state(
+    farm_state: &AccountInfo,
+    reserve: &Reserve,
+    lending_market_authority: &Pubkey,
+    mode: ReserveFarmKind,
+) -> Result<()> {
+    let farm_state_loader = AccountLoader::<farms::state::FarmState>::try_from(farm_state)?;
+    let farm_state = farm_state_loader.load()?;
+
+    if farm_state.delegate_authority != *lending_market_authority {
+        msg!(
+            "Farm delegate authority {} is not the lending market authority {}",
+            farm_state.delegate_authority,
+            lending_market_authority
+        );
+        return err!(LendingError::InvalidFarmForReserve);
+    }
+
+    let expected_mint = match mode {
+        ReserveFarmKind::Collateral => reserve.collateral.mint_pubkey,
+        ReserveFarmKind::Debt => reserve.liquidity.mint_pubkey,
+    };
+    if farm_state.token.mint != Pubkey::default() && farm_state.token.mint != expected_mint {
+        msg!(
+            "Farm mint {} does not match the reserve {:?} mint {}",
+            farm_state.token.mint,
+            mode,
+            expected_mint
+        );
+        return err!(LendingError::InvalidFarmForReserve);
+    }
+
+    Ok(())
+}
+
 pub fn cpi_initialize_farm_delegated(ctx: &Context<InitFarmsForReserve>) -> Result<()> {
     let lending_market = ctx.accounts.lending_market.load()?;
     let lending_market_key = ctx.accounts.lending_market.key();
@@ -100,6 +137,54 @@ pub fn cpi_initialize_farmer_delegated(
     .map_err(Into::into)
 }
 
+pub fn cpi_initialize_farmer_delegated_batch<'info>(
+    ctx: &Context<'_, '_, '_, 'info, InitObligationFarmsForReservesBatch<'info>>,
+    obligation: &Pubkey,
+    farm_state: &AccountInfo<'info>,
+    obligation_farm: &AccountInfo<'info>,
+) -> Result<()> {
+    let lending_market = ctx.accounts.lending_market.load()?;
+    let lending_market_key = ctx.accounts.lending_market.key();
+
+    let accounts = farms::accounts::InitializeUser {
+        authority: ctx
+            .accounts
+            .lending_market_authority
+            .to_account_info()
+            .key(),
+        payer: ctx.accounts.payer.key(),
+        user_state: obligation_farm.key(),
+        farm_state: farm_state.key(),
+        owner: ctx.accounts.owner.key(),
+        delegatee: *obligation,
+        system_program: ctx.accounts.system_program.to_account_info().key(),
+        rent: ctx.accounts.rent.to_account_info().key(),
+    }
+    .to_account_metas(None);
+
+    let data = farms::instruction::InitializeUser {}.data();
+
+    let instruction = Instruction {
+        program_id: ctx.accounts.farms_program.key(),
+        accounts,
+        data,
+    };
+
+    let lending_market_authority_signer_seeds =
+        gen_signer_seeds!(lending_market_key.as_ref(), lending_market.bump_seed as u8);
+
+    let mut account_infos = ctx.accounts.to_account_infos();
+    account_infos.push(farm_state.clone());
+    account_infos.push(obligation_farm.clone());
+
+    program::invoke_signed(
+        &instruction,
+        &account_infos,
+        &[lending_market_authority_signer_seeds],
+    )
+    .map_err(Into::into)
+}
+
 pub fn cpi_set_stake_delegated(
     ctx: &Context<RefreshObligationFarmsForReserve>,
     reserve: &Reserve,
@@ -142,3 +227,93 @@ pub fn cpi_set_stake_delegated(
     )
     .map_err(Into::into)
 }
+
+pub fn cpi_initialize_farmer_delegated_for_migration<'info>(
+    ctx: &Context<'_, '_, '_, 'info, MigrateObligationSeeds<'info>>,
+    farm_state: &AccountInfo<'info>,
+    new_obligation_farm: &AccountInfo<'info>,
+) -> Result<()> {
+    let lending_market = ctx.accounts.lending_market.load()?;
+    let lending_market_key = ctx.accounts.lending_market.key();
+
+    let accounts = farms::accounts::InitializeUser {
+        authority: ctx
+            .accounts
+            .lending_market_authority
+            .to_account_info()
+            .key(),
+        payer: ctx.accounts.fee_payer.key(),
+        user_state: new_obligation_farm.key(),
+        farm_state: farm_state.key(),
+        owner: ctx.accounts.obligation_owner.key(),
+        delegatee: ctx.accounts.new_obligation.key(),
+        system_program: ctx.accounts.system_program.to_account_info().key(),
+        rent: ctx.accounts.rent.to_account_info().key(),
+    }
+    .to_account_metas(None);
+
+    let data = farms::instruction::InitializeUser {}.data();
+
+    let instruction = Instruction {
+        program_id: ctx.accounts.farms_program.key(),
+        accounts,
+        data,
+    };
+
+    let lending_market_authority_signer_seeds =
+        gen_signer_seeds!(lending_market_key.as_ref(), lending_market.bump_seed as u8);
+
+    let mut account_infos = ctx.accounts.to_account_infos();
+    account_infos.push(farm_state.clone());
+    account_infos.push(new_obligation_farm.clone());
+
+    program::invoke_signed(
+        &instruction,
+        &account_infos,
+        &[lending_market_authority_signer_seeds],
+    )
+    .map_err(Into::into)
+}
+
+pub fn cpi_set_stake_delegated_for_migration<'info>(
+    ctx: &Context<'_, '_, '_, 'info, MigrateObligationSeeds<'info>>,
+    farm_state: &AccountInfo<'info>,
+    obligation_farm: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let lending_market = ctx.accounts.lending_market.load()?;
+    let lending_market_key = ctx.accounts.lending_market.key();
+
+    let accounts = farms::accounts::SetStakeDelegated {
+        delegate_authority: ctx
+            .accounts
+            .lending_market_authority
+            .to_account_info()
+            .key(),
+        user_state: obligation_farm.key(),
+        farm_state: farm_state.key(),
+    }
+    .to_account_metas(None);
+
+    let data = farms::instruction::SetStakeDelegated { new_amount: amount }.data();
+
+    let instruction = Instruction {
+        program_id: ctx.accounts.farms_program.key(),
+        accounts,
+        data,
+    };
+
+    let lending_market_authority_signer_seeds =
+        gen_signer_seeds!(lending_market_key.as_ref(), lending_market.bump_seed as u8);
+
+    let mut account_infos = ctx.accounts.to_account_infos();
+    account_infos.push(farm_state.clone());
+    account_infos.push(obligation_farm.clone());
+
+    program::invoke_signed(
+        &instruction,
+        &account_infos,
+        &[lending_market_authority_signer_seeds],
+    )
+    .map_err(Into::into)
+}
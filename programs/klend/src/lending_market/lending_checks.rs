@@ -9,18 +9,20 @@ use crate::utils::constraints;
 use crate::{
     handlers::*,
     state::{
-        DepositObligationCollateralAccounts, RedeemReserveCollateralAccounts,
-        WithdrawObligationCollateralAccounts,
+        BorrowObligationLiquidityAccounts, DepositObligationCollateralAccounts,
+        RedeemReserveCollateralAccounts, WithdrawObligationCollateralAccounts,
         WithdrawObligationCollateralAndRedeemReserveCollateralAccounts,
     },
     utils::{seeds::BASE_SEED_REFERRER_TOKEN_STATE, FatAccountLoader, PROGRAM_VERSION},
     LendingAction, LendingError, Obligation, ReferrerTokenState, Reserve, ReserveStatus,
 };
 
-pub fn borrow_obligation_liquidity_checks(ctx: &Context<BorrowObligationLiquidity>) -> Result<()> {
-    let borrow_reserve = &ctx.accounts.borrow_reserve.load()?;
+pub fn borrow_obligation_liquidity_checks(
+    accounts: &BorrowObligationLiquidityAccounts,
+) -> Result<()> {
+    let borrow_reserve = &accounts.borrow_reserve.load()?;
 
-    if borrow_reserve.liquidity.supply_vault == ctx.accounts.user_destination_liquidity.key() {
+    if borrow_reserve.liquidity.supply_vault == accounts.user_destination_liquidity.key() {
         msg!(
             "Borrow reserve liquidity supply cannot be used as the destination liquidity provided"
         );
@@ -38,8 +40,8 @@ pub fn borrow_obligation_liquidity_checks(ctx: &Context<BorrowObligationLiquidit
     }
 
     constraints::token_2022::validate_liquidity_token_extensions(
-        &ctx.accounts.borrow_reserve_liquidity_mint.to_account_info(),
-        &ctx.accounts.user_destination_liquidity.to_account_info(),
+        &accounts.borrow_reserve_liquidity_mint.to_account_info(),
+        &accounts.user_destination_liquidity.to_account_info(),
     )?;
 
     Ok(())
@@ -28,16 +28,21 @@ use crate::{
     liquidation_operations,
     state::{
         obligation::Obligation, CalculateBorrowResult, CalculateLiquidationResult,
-        CalculateRepayResult, Reserve,
+        CalculateRepayResult, FeeCalculation, Reserve,
     },
     utils::{
-        borrow_rate_curve::BorrowRateCurve, AnyAccountLoader, BigFraction, Fraction,
-        GetPriceResult, ELEVATION_GROUP_NONE, PROGRAM_VERSION,
+        borrow_rate_curve::BorrowRateCurve, ten_pow, AnyAccountLoader, BigFraction, Fraction,
+        GetPriceResult, ELEVATION_GROUP_NONE, FULL_BPS, MAX_NUM_ELEVATION_GROUPS,
+        MAX_OBLIGATION_BORROW_FACTOR_MULTIPLIER_BPS, MIN_OBLIGATION_BORROW_FACTOR_MULTIPLIER_BPS,
+        PROGRAM_VERSION, SLOTS_PER_YEAR,
     },
-    xmsg, AssetTier, ElevationGroup, LendingError, LendingMarket, LiquidateAndRedeemResult,
-    LiquidateObligationResult, ObligationCollateral, PriceStatusFlags, ReferrerTokenState,
-    RefreshObligationBorrowsResult, RefreshObligationDepositsResult, ReserveConfig, ReserveStatus,
-    UpdateConfigMode, WithdrawResult,
+    xmsg, AssetTier, BorrowBlockedReason, CompoundYieldResult, CumulativeBorrowRateSnapshot,
+    ElevationGroup, LendingError, LendingMarket, LiquidateAndRedeemResult,
+    LiquidateObligationResult, MarketTotals, ObligationCollateral, ObligationSnapshot,
+    ObligationValueDrift, PriceStatusFlags, PriceTimestampsRange, ReferrerTokenState,
+    RefreshObligationBorrowsResult, RefreshObligationDepositsResult, ReserveConfig,
+    ReserveEffectiveRiskParams, ReserveStatus, ReserveVaultBalanceSnapshot, UpdateConfigMode,
+    WithdrawResult,
 };
 
 pub fn refresh_reserve(
@@ -47,8 +52,9 @@ pub fn refresh_reserve(
     referral_fee_bps: u16,
 ) -> Result<()> {
     let slot = clock.slot;
+    let is_first_refresh_in_slot = slot > reserve.last_update.slot;
 
-    reserve.accrue_interest(slot, referral_fee_bps)?;
+    let accrued_slot = reserve.accrue_interest(slot, referral_fee_bps)?;
 
     let price_status = if let Some(GetPriceResult {
         price,
@@ -59,14 +65,41 @@ pub fn refresh_reserve(
         reserve.liquidity.market_price_sf = price.to_bits();
         reserve.liquidity.market_price_last_updated_ts = timestamp;
 
-        Some(status)
+        status
     } else if !is_saved_price_age_valid(reserve, clock.unix_timestamp) {
-        Some(PriceStatusFlags::empty())
+        if is_saved_price_in_grace_period(reserve, clock.unix_timestamp) {
+            PriceStatusFlags::LAST_VALID_PRICE_ALLOWED
+        } else {
+            PriceStatusFlags::empty()
+        }
     } else {
-        None
+        reserve.last_update.get_price_status()
     };
 
-    reserve.last_update.update_slot(slot, price_status);
+    if price_status.contains(PriceStatusFlags::ALL_CHECKS) {
+        reserve.liquidity.consecutive_stale_price_refreshes = 0;
+    } else if is_first_refresh_in_slot {
+        reserve.liquidity.consecutive_stale_price_refreshes = reserve
+            .liquidity
+            .consecutive_stale_price_refreshes
+            .saturating_add(1);
+
+        let max_consecutive_stale_price_refreshes =
+            reserve.config.max_consecutive_stale_price_refreshes;
+        if max_consecutive_stale_price_refreshes > 0
+            && reserve.liquidity.consecutive_stale_price_refreshes
+                >= max_consecutive_stale_price_refreshes
+            && reserve.liquidity.stale_price_borrowing_disabled == 0
+        {
+            msg!(
+                "Price stale for {} consecutive refreshes, disabling borrowing",
+                reserve.liquidity.consecutive_stale_price_refreshes
+            );
+            reserve.liquidity.stale_price_borrowing_disabled = 1;
+        }
+    }
+
+    reserve.last_update.update_slot(accrued_slot, price_status);
 
     reserve.config.reserved_2 = [0; 2];
     reserve.config.reserved_3 = [0; 8];
@@ -74,6 +107,25 @@ pub fn refresh_reserve(
     Ok(())
 }
 
+pub fn emit_cumulative_borrow_rate_snapshot(
+    reserve_pk: Pubkey,
+    reserve: &Reserve,
+    lending_market: &LendingMarket,
+    previous_cumulative_borrow_rate_bsf: [u64; 4],
+    slot: Slot,
+) {
+    let cumulative_borrow_rate_bsf = reserve.liquidity.cumulative_borrow_rate_bsf.value;
+    if lending_market.is_cumulative_borrow_rate_events_enabled()
+        && cumulative_borrow_rate_bsf != previous_cumulative_borrow_rate_bsf
+    {
+        emit!(CumulativeBorrowRateSnapshot {
+            reserve: reserve_pk,
+            slot,
+            cumulative_borrow_rate_bsf,
+        });
+    }
+}
+
 pub fn is_saved_price_age_valid(reserve: &Reserve, current_ts: clock::UnixTimestamp) -> bool {
     let current_ts: u64 = current_ts.try_into().expect("Negative timestamp");
     let price_last_updated_ts = reserve.liquidity.market_price_last_updated_ts;
@@ -82,6 +134,19 @@ pub fn is_saved_price_age_valid(reserve: &Reserve, current_ts: clock::UnixTimest
     current_ts.saturating_sub(price_last_updated_ts) < price_max_age
 }
 
+pub fn is_saved_price_in_grace_period(reserve: &Reserve, current_ts: clock::UnixTimestamp) -> bool {
+    let current_ts: u64 = current_ts.try_into().expect("Negative timestamp");
+    let price_last_updated_ts = reserve.liquidity.market_price_last_updated_ts;
+    let price_max_age_with_grace = reserve
+        .config
+        .token_info
+        .max_age_price_seconds
+        .saturating_add(reserve.config.token_info.max_age_price_grace_seconds);
+
+    reserve.liquidity.market_price_sf != 0
+        && current_ts.saturating_sub(price_last_updated_ts) < price_max_age_with_grace
+}
+
 pub fn is_price_refresh_needed(
     reserve: &Reserve,
     market: &LendingMarket,
@@ -98,6 +163,17 @@ pub fn is_price_refresh_needed(
     current_ts.saturating_sub(price_last_updated_ts) >= price_refresh_trigger_to_max_age_secs
 }
 
+pub fn get_reserves_needing_price_refresh<'a>(
+    reserves: impl IntoIterator<Item = &'a Reserve>,
+    market: &LendingMarket,
+    current_ts: clock::UnixTimestamp,
+) -> Vec<&'a Reserve> {
+    reserves
+        .into_iter()
+        .filter(|reserve| is_price_refresh_needed(reserve, market, current_ts))
+        .collect()
+}
+
 pub fn refresh_reserve_limit_timestamps(reserve: &mut Reserve, slot: Slot) -> Result<()> {
     reserve.update_deposit_limit_crossed_slot(slot)?;
     reserve.update_borrow_limit_crossed_slot(slot)?;
@@ -123,7 +199,7 @@ pub fn deposit_reserve_liquidity(
     }
 
     let liquidity_amount_f = Fraction::from(liquidity_amount);
-    let deposit_limit_f = Fraction::from(reserve.config.deposit_limit);
+    let deposit_limit_f = reserve.deposit_limit_f()?;
     let reserve_liquidity_supply_f = reserve.liquidity.total_supply()?;
 
     let new_reserve_liquidity_supply_f = liquidity_amount_f + reserve_liquidity_supply_f;
@@ -132,7 +208,7 @@ pub fn deposit_reserve_liquidity(
         msg!(
             "Cannot deposit liquidity above the reserve deposit limit. New total deposit: {} > limit: {}",
             new_reserve_liquidity_supply_f,
-            reserve.config.deposit_limit
+            deposit_limit_f.to_display()
         );
         return err!(LendingError::DepositLimitExceeded);
     }
@@ -150,6 +226,173 @@ pub fn deposit_reserve_liquidity(
     Ok(collateral_amount)
 }
 
+/// Risk parameters the program applies to the reserve for an obligation in the given elevation
+/// group, `ELEVATION_GROUP_NONE` resolving to the reserve's own parameters.
+pub fn get_reserve_effective_risk_params(
+    lending_market: &LendingMarket,
+    reserve: &Reserve,
+    elevation_group_id: u8,
+) -> Result<ReserveEffectiveRiskParams> {
+    let elevation_group = get_elevation_group(elevation_group_id, lending_market)?;
+
+    if elevation_group.is_some()
+        && !reserve
+            .config
+            .elevation_groups
+            .contains(&elevation_group_id)
+    {
+        msg!(
+            "Reserve is not part of elevation group {}",
+            elevation_group_id
+        );
+        return err!(LendingError::InconsistentElevationGroup);
+    }
+
+    let (loan_to_value_pct, liquidation_threshold_pct) =
+        get_max_ltv_and_liquidation_threshold(reserve, elevation_group)?;
+
+    let max_liquidation_bonus_bps = min(
+        reserve.config.max_liquidation_bonus_bps,
+        liquidation_operations::get_emode_max_liquidation_bonus(
+            lending_market,
+            &reserve.config,
+            &reserve.config,
+            elevation_group_id,
+        ),
+    );
+
+    Ok(ReserveEffectiveRiskParams {
+        loan_to_value_pct,
+        liquidation_threshold_pct,
+        max_liquidation_bonus_bps,
+        borrow_factor_f: reserve.borrow_factor_f(elevation_group.is_some()),
+    })
+}
+
+/// Net APY in bps of a looped position at `leverage` (total collateral over equity): the supply
+/// APY earned on the collateral minus the borrow APY paid on the debt. Negative when the borrow
+/// cost exceeds the yield.
+pub fn calculate_leveraged_net_apy_bps(
+    lending_market: &LendingMarket,
+    collateral_reserve: &Reserve,
+    debt_reserve: &Reserve,
+    elevation_group_id: u8,
+    leverage: Fraction,
+) -> Result<i64> {
+    if leverage < Fraction::ONE {
+        msg!("Leverage must be at least 1");
+        return err!(LendingError::InvalidAmount);
+    }
+
+    let ReserveEffectiveRiskParams {
+        loan_to_value_pct, ..
+    } = get_reserve_effective_risk_params(lending_market, collateral_reserve, elevation_group_id)?;
+    let ReserveEffectiveRiskParams {
+        borrow_factor_f, ..
+    } = get_reserve_effective_risk_params(lending_market, debt_reserve, elevation_group_id)?;
+
+    let max_debt_to_collateral = Fraction::from_percent(loan_to_value_pct) / borrow_factor_f;
+    let debt_to_collateral = (leverage - Fraction::ONE) / leverage;
+    if debt_to_collateral > max_debt_to_collateral {
+        msg!(
+            "Leverage {} exceeds the maximum allowed by the collateral LTV {}% and debt borrow factor {}",
+            leverage.to_display(),
+            loan_to_value_pct,
+            borrow_factor_f.to_display()
+        );
+        return err!(LendingError::InvalidAmount);
+    }
+
+    let supply_yield_f = collateral_reserve.current_supply_apy()? * leverage;
+    let borrow_cost_f = debt_reserve.current_borrow_apy()? * (leverage - Fraction::ONE);
+
+    let supply_yield_bps: i64 = supply_yield_f.to_bps().ok_or(LendingError::MathOverflow)?;
+    let borrow_cost_bps: i64 = borrow_cost_f.to_bps().ok_or(LendingError::MathOverflow)?;
+
+    Ok(supply_yield_bps - borrow_cost_bps)
+}
+
+/// First reason found for which `borrow_obligation_liquidity` would currently reject any borrow
+/// from the reserve, `None` if borrowing is allowed. Obligation specific conditions are only
+/// checked when an obligation is given.
+pub fn get_borrow_blocked_reason(
+    lending_market: &LendingMarket,
+    borrow_reserve: &Reserve,
+    borrow_reserve_pk: Pubkey,
+    obligation: Option<&Obligation>,
+    slot: Slot,
+) -> Result<Option<BorrowBlockedReason>> {
+    if lending_market.is_borrowing_disabled() {
+        return Ok(Some(BorrowBlockedReason::MarketBorrowingDisabled));
+    }
+
+    if borrow_reserve.config.status() == ReserveStatus::Obsolete {
+        return Ok(Some(BorrowBlockedReason::ReserveObsolete));
+    }
+
+    if borrow_reserve.version != PROGRAM_VERSION as u64 {
+        return Ok(Some(BorrowBlockedReason::ReserveDeprecated));
+    }
+
+    let required_price_status = if lending_market.is_strict_borrow_price_checks() {
+        PriceStatusFlags::STRICT_BORROW_CHECKS
+    } else {
+        PriceStatusFlags::ALL_CHECKS
+    };
+    if borrow_reserve
+        .last_update
+        .is_stale(slot, required_price_status)?
+    {
+        return Ok(Some(BorrowBlockedReason::ReserveStale));
+    }
+
+    if borrow_reserve.liquidity.stale_price_borrowing_disabled != 0 {
+        return Ok(Some(BorrowBlockedReason::StalePriceBorrowingDisabled));
+    }
+
+    if borrow_reserve.liquidity.total_borrow() >= borrow_reserve.borrow_limit_f()? {
+        return Ok(Some(BorrowBlockedReason::BorrowLimitReached));
+    }
+
+    let utilization_limit = borrow_reserve
+        .config
+        .utilization_limit_block_borrowing_above;
+    if utilization_limit != 0
+        && borrow_reserve.liquidity.utilization_rate()? >= Fraction::from_percent(utilization_limit)
+    {
+        return Ok(Some(BorrowBlockedReason::UtilizationLimitReached));
+    }
+
+    let Some(obligation) = obligation else {
+        return Ok(None);
+    };
+
+    if borrow_reserve.config.status() == ReserveStatus::Hidden
+        && !obligation
+            .borrows
+            .iter()
+            .any(|b| b.borrow_reserve == borrow_reserve_pk)
+    {
+        return Ok(Some(BorrowBlockedReason::ReserveHidden));
+    }
+
+    if check_same_elevation_group(obligation, borrow_reserve).is_err() {
+        return Ok(Some(BorrowBlockedReason::InconsistentElevationGroup));
+    }
+
+    if check_elevation_group_borrowing_enabled(lending_market, obligation).is_err() {
+        return Ok(Some(BorrowBlockedReason::ElevationGroupNewLoansDisabled));
+    }
+
+    if check_non_elevation_group_borrowing_enabled(obligation).is_err() {
+        return Ok(Some(
+            BorrowBlockedReason::BorrowingDisabledOutsideElevationGroup,
+        ));
+    }
+
+    Ok(None)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn borrow_obligation_liquidity<'info, T>(
     lending_market: &LendingMarket,
@@ -169,9 +412,15 @@ where
         return err!(LendingError::InvalidAmount);
     }
 
+    let required_price_status = if lending_market.is_strict_borrow_price_checks() {
+        PriceStatusFlags::STRICT_BORROW_CHECKS
+    } else {
+        PriceStatusFlags::ALL_CHECKS
+    };
+
     if borrow_reserve
         .last_update
-        .is_stale(clock.slot, PriceStatusFlags::ALL_CHECKS)?
+        .is_stale(clock.slot, required_price_status)?
     {
         msg!(
             "Borrow reserve is stale and must be refreshed in the current slot, price_status: {:08b}",
@@ -185,22 +434,40 @@ where
         return err!(LendingError::BorrowingDisabled);
     }
 
+    if borrow_reserve.liquidity.stale_price_borrowing_disabled != 0 {
+        msg!("Borrowing is disabled on the reserve after consecutive stale prices");
+        return err!(LendingError::BorrowingDisabled);
+    }
+
+    utils::check_obligation_obsolete_reserves_limit(lending_market, obligation)?;
+
     let current_utilization = borrow_reserve.liquidity.utilization_rate()?;
     let reserve_liquidity_borrowed_f = borrow_reserve.liquidity.total_borrow();
     let liquidity_amount_f = Fraction::from(liquidity_amount);
-    let borrow_limit_f = Fraction::from(borrow_reserve.config.borrow_limit);
+    let borrow_limit_f = borrow_reserve.borrow_limit_f()?;
 
     let new_borrowed_amount_f = liquidity_amount_f + reserve_liquidity_borrowed_f;
     if liquidity_amount != u64::MAX && new_borrowed_amount_f > borrow_limit_f {
         msg!(
             "Cannot borrow above the borrow limit. New total borrow: {} > limit: {}",
             new_borrowed_amount_f.to_display(),
-            borrow_reserve.config.borrow_limit
+            borrow_limit_f.to_display()
         );
         return err!(LendingError::BorrowLimitExceeded);
     }
     check_obligation_fully_refreshed_and_not_null(obligation, clock.slot)?;
 
+    if obligation
+        .last_update
+        .is_stale(clock.slot, required_price_status)?
+    {
+        msg!(
+            "Obligation prices are not verified against a TWAP, price status: {:08b}",
+            obligation.last_update.get_price_status().0
+        );
+        return err!(LendingError::ObligationStale);
+    }
+
     let remaining_borrow_value = obligation.remaining_borrow_value();
     if remaining_borrow_value == Fraction::ZERO {
         msg!("Remaining borrow value is zero");
@@ -236,6 +503,19 @@ where
     let borrow_amount = borrow_amount_f.to_ceil();
     msg!("Requested {}, allowed {}", liquidity_amount, borrow_amount);
 
+    let has_existing_borrow = obligation
+        .borrows
+        .iter()
+        .any(|b| b.borrow_reserve == borrow_reserve_pk);
+    if !has_existing_borrow && borrow_amount < borrow_reserve.config.min_borrow_amount {
+        msg!(
+            "Borrow amount {} is below the reserve minimum borrow amount {}",
+            borrow_amount,
+            borrow_reserve.config.min_borrow_amount
+        );
+        return err!(LendingError::BorrowAmountBelowMinimum);
+    }
+
     add_to_withdrawal_accum(
         &mut borrow_reserve.config.debt_withdrawal_cap,
         borrow_amount,
@@ -258,6 +538,7 @@ where
             borrow_reserve_pk,
             cumulative_borrow_rate_bf,
             borrow_reserve.config.get_asset_tier(),
+            borrow_reserve.config.status(),
         )?;
 
         obligation_liquidity.borrow(borrow_amount_f);
@@ -313,6 +594,7 @@ where
         borrow_reserve,
         Fraction::from_bits(obligation.borrows[borrow_index].market_value_sf),
         Fraction::from_bits(lending_market.min_net_value_in_obligation_sf),
+        lending_market.min_borrow_health_buffer_bps,
     )?;
 
     Ok(CalculateBorrowResult {
@@ -344,6 +626,8 @@ pub fn deposit_obligation_collateral(
         return err!(LendingError::ReserveStale);
     }
 
+    utils::check_obligation_obsolete_reserves_limit(lending_market, obligation)?;
+
     if deposit_reserve.config.disable_usage_as_coll_outside_emode > 0
         && obligation.elevation_group == ELEVATION_GROUP_NONE
         && obligation.borrow_factor_adjusted_debt_value_sf > 0
@@ -352,11 +636,18 @@ pub fn deposit_obligation_collateral(
         return err!(LendingError::DepositDisabledOutsideElevationGroup);
     }
 
+    if deposit_reserve.config.disable_usage_as_collateral > 0 {
+        msg!("Deposit reserve is disabled for usage as collateral");
+        return err!(LendingError::DepositDisabledAsCollateral);
+    }
+
     check_same_elevation_group(obligation, deposit_reserve)?;
     let elevation_group = get_elevation_group(obligation.elevation_group, lending_market)?;
     let pre_deposit_count = obligation.deposits_count();
     let total_borrowed_amount = obligation.get_borrowed_amount_if_single_token();
     let asset_tier = deposit_reserve.config.get_asset_tier();
+    let deposit_reserve_status = deposit_reserve.config.status();
+    let max_deposit_per_obligation = deposit_reserve.config.max_deposit_per_obligation;
 
     let new_deposit_initializer = |obligation_collateral: &mut ObligationCollateral| -> Result<()> {
         utils::update_elevation_group_debt_trackers_on_new_deposit(
@@ -373,11 +664,23 @@ pub fn deposit_obligation_collateral(
         let obligation_collateral = obligation.find_or_add_collateral_to_deposits(
             deposit_reserve_pk,
             asset_tier,
+            deposit_reserve_status,
             new_deposit_initializer,
         )?;
 
         obligation_collateral.deposit(collateral_amount)?;
 
+        if max_deposit_per_obligation != 0
+            && obligation_collateral.deposited_amount > max_deposit_per_obligation
+        {
+            msg!(
+                "Obligation deposit {} exceeds the reserve max deposit per obligation {}",
+                obligation_collateral.deposited_amount,
+                max_deposit_per_obligation
+            );
+            return err!(LendingError::ObligationDepositLimitExceeded);
+        }
+
         Fraction::from_bits(obligation_collateral.market_value_sf)
     };
 
@@ -399,6 +702,30 @@ pub fn deposit_obligation_collateral(
     Ok(())
 }
 
+pub fn deposit_reserve_liquidity_and_obligation_collateral(
+    lending_market: &LendingMarket,
+    reserve: &mut Reserve,
+    obligation: &mut Obligation,
+    clock: &Clock,
+    liquidity_amount: u64,
+    reserve_pk: Pubkey,
+) -> Result<u64> {
+    let collateral_amount = deposit_reserve_liquidity(reserve, clock, liquidity_amount)?;
+
+    refresh_reserve(reserve, clock, None, lending_market.referral_fee_bps)?;
+
+    deposit_obligation_collateral(
+        lending_market,
+        reserve,
+        obligation,
+        clock.slot,
+        collateral_amount,
+        reserve_pk,
+    )?;
+
+    Ok(collateral_amount)
+}
+
 pub fn withdraw_obligation_collateral(
     lending_market: &LendingMarket,
     withdraw_reserve: &mut Reserve,
@@ -455,7 +782,8 @@ pub fn withdraw_obligation_collateral(
         return err!(LendingError::ObligationInDeprecatedReserve);
     }
 
-    let withdraw_amount = if is_borrows_empty {
+    let is_supply_only = !obligation.is_counted_as_collateral(collateral);
+    let withdraw_amount = if is_borrows_empty || is_supply_only {
         if collateral_amount == u64::MAX {
             collateral.deposited_amount
         } else {
@@ -470,6 +798,14 @@ pub fn withdraw_obligation_collateral(
             get_elevation_group(obligation.elevation_group, lending_market)?,
         )?;
 
+        if lending_market.is_zero_ltv_collateral_withdrawal_first()
+            && obligation.has_zero_ltv_deposit()
+            && reserve_loan_to_value_pct > 0
+        {
+            msg!("Zero LTV collateral must be withdrawn before other collaterals");
+            return err!(LendingError::ObligationCollateralLtvZero);
+        }
+
         let max_withdraw_value = obligation.max_withdraw_value(reserve_loan_to_value_pct)?;
 
         if max_withdraw_value == Fraction::ZERO {
@@ -478,9 +814,12 @@ pub fn withdraw_obligation_collateral(
         }
 
         let collateral_value = Fraction::from_bits(collateral.market_value_sf);
-        let withdraw_amount = if collateral_amount == u64::MAX {
-            let withdraw_value = max_withdraw_value.min(collateral_value);
-            let withdraw_ratio = withdraw_value / collateral_value;
+        let withdraw_amount = if collateral_amount == u64::MAX
+            && max_withdraw_value >= collateral_value
+        {
+            collateral.deposited_amount
+        } else if collateral_amount == u64::MAX {
+            let withdraw_ratio = max_withdraw_value / collateral_value;
 
             let ratioed_amount_f = withdraw_ratio * u128::from(collateral.deposited_amount);
             let ratioed_amount: u64 = ratioed_amount_f.to_floor();
@@ -524,17 +863,164 @@ pub fn withdraw_obligation_collateral(
         )?;
     }
 
-    post_withdraw_obligation_invariants(
-        withdraw_reserve
-            .collateral_exchange_rate()?
-            .fraction_collateral_to_liquidity(Fraction::from(withdraw_amount)),
-        obligation,
+    if !is_supply_only {
+        post_withdraw_obligation_invariants(
+            withdraw_reserve
+                .collateral_exchange_rate()?
+                .fraction_collateral_to_liquidity(Fraction::from(withdraw_amount)),
+            obligation,
+            withdraw_reserve,
+            Fraction::from_bits(obligation.deposits[collateral_index].market_value_sf),
+            Fraction::from_bits(lending_market.min_net_value_in_obligation_sf),
+        )?;
+    }
+
+    Ok(withdraw_amount)
+}
+
+/// For each deposit, the price of its reserve at which the obligation becomes liquidatable, all
+/// other values from the last refresh staying constant. `None` when no price drop of that
+/// collateral alone can make the obligation liquidatable.
+pub fn calculate_collateral_liquidation_prices<'info, T>(
+    lending_market: &LendingMarket,
+    obligation: &Obligation,
+    mut deposit_reserves_iter: impl Iterator<Item = T>,
+) -> Result<Vec<(Pubkey, Option<Fraction>)>>
+where
+    T: AnyAccountLoader<'info, Reserve>,
+{
+    let elevation_group = get_elevation_group(obligation.elevation_group, lending_market)?;
+    let debt_value_f = Fraction::from_bits(obligation.borrow_factor_adjusted_debt_value_sf);
+    let unhealthy_borrow_value_f = Fraction::from_bits(obligation.unhealthy_borrow_value_sf);
+
+    let mut liquidation_prices = Vec::new();
+    for deposit in obligation
+        .deposits
+        .iter()
+        .filter(|deposit| deposit.deposit_reserve != Pubkey::default())
+    {
+        let deposit_reserve_loader = deposit_reserves_iter
+            .next()
+            .ok_or(error!(LendingError::InvalidAccountInput))?;
+        require_keys_eq!(
+            deposit_reserve_loader.get_pubkey(),
+            deposit.deposit_reserve,
+            LendingError::InvalidAccountInput
+        );
+        let deposit_reserve = deposit_reserve_loader.get()?;
+
+        let (_, liquidation_threshold_pct) =
+            get_max_ltv_and_liquidation_threshold(&deposit_reserve, elevation_group)?;
+        let deposit_unhealthy_value_f = Fraction::from_bits(deposit.market_value_sf)
+            * Fraction::from_percent(liquidation_threshold_pct);
+
+        let liquidation_price = if !obligation.is_counted_as_collateral(deposit)
+            || deposit_unhealthy_value_f == Fraction::ZERO
+        {
+            None
+        } else {
+            let other_unhealthy_value_f =
+                unhealthy_borrow_value_f.saturating_sub(deposit_unhealthy_value_f);
+            if debt_value_f <= other_unhealthy_value_f {
+                None
+            } else {
+                let price_ratio =
+                    (debt_value_f - other_unhealthy_value_f) / deposit_unhealthy_value_f;
+                Some(deposit_reserve.liquidity.get_market_price_f() * price_ratio)
+            }
+        };
+
+        liquidation_prices.push((deposit.deposit_reserve, liquidation_price));
+    }
+
+    Ok(liquidation_prices)
+}
+
+/// Largest `collateral_amount` that `withdraw_obligation_collateral` accepts for the given deposit
+/// without breaching the obligation LTV, 0 if the collateral cannot currently be withdrawn.
+pub fn calculate_max_withdraw_collateral_amount(
+    lending_market: &LendingMarket,
+    withdraw_reserve: &Reserve,
+    obligation: &Obligation,
+    slot: Slot,
+    withdraw_reserve_pk: Pubkey,
+) -> Result<u64> {
+    let is_borrows_empty = obligation.borrows_empty();
+
+    let required_price_status = if is_borrows_empty {
+        PriceStatusFlags::NONE
+    } else {
+        PriceStatusFlags::ALL_CHECKS
+    };
+
+    if withdraw_reserve
+        .last_update
+        .is_stale(slot, required_price_status)?
+    {
+        msg!(
+            "Withdraw reserve is stale and must be refreshed in the current slot, price status: {:08b}",
+            withdraw_reserve.last_update.get_price_status().0
+        );
+        return err!(LendingError::ReserveStale);
+    }
+
+    if obligation
+        .last_update
+        .is_stale(slot, required_price_status)?
+    {
+        msg!(
+            "Obligation is stale and must be refreshed in the current slot, price status: {:08b}",
+            obligation.last_update.get_price_status().0
+        );
+        return err!(LendingError::ObligationStale);
+    }
+
+    let collateral_index = obligation.position_of_collateral_in_deposits(withdraw_reserve_pk)?;
+    let collateral = &obligation.deposits[collateral_index];
+    if collateral.deposited_amount == 0 {
+        return Ok(0);
+    }
+
+    check_elevation_group_borrowing_enabled(lending_market, obligation)?;
+
+    if obligation.num_of_obsolete_reserves > 0
+        && withdraw_reserve.config.status() == ReserveStatus::Active
+    {
+        return err!(LendingError::ObligationInDeprecatedReserve);
+    }
+
+    if is_borrows_empty || !obligation.is_counted_as_collateral(collateral) {
+        return Ok(collateral.deposited_amount);
+    }
+
+    if obligation.deposited_value_sf == 0 {
+        msg!("Obligation deposited value is zero");
+        return err!(LendingError::ObligationDepositsZero);
+    }
+
+    let (reserve_loan_to_value_pct, _) = get_max_ltv_and_liquidation_threshold(
         withdraw_reserve,
-        Fraction::from_bits(obligation.deposits[collateral_index].market_value_sf),
-        Fraction::from_bits(lending_market.min_net_value_in_obligation_sf),
+        get_elevation_group(obligation.elevation_group, lending_market)?,
     )?;
 
-    Ok(withdraw_amount)
+    if lending_market.is_zero_ltv_collateral_withdrawal_first()
+        && obligation.has_zero_ltv_deposit()
+        && reserve_loan_to_value_pct > 0
+    {
+        return Ok(0);
+    }
+
+    let max_withdraw_value = obligation.max_withdraw_value(reserve_loan_to_value_pct)?;
+    let collateral_value = Fraction::from_bits(collateral.market_value_sf);
+
+    if max_withdraw_value >= collateral_value {
+        return Ok(collateral.deposited_amount);
+    }
+
+    let withdraw_ratio = max_withdraw_value / collateral_value;
+    let ratioed_amount: u64 = (withdraw_ratio * u128::from(collateral.deposited_amount)).to_floor();
+
+    Ok(min(collateral.deposited_amount, ratioed_amount))
 }
 
 pub fn redeem_reserve_collateral(
@@ -542,6 +1028,7 @@ pub fn redeem_reserve_collateral(
     collateral_amount: u64,
     clock: &Clock,
     add_amount_to_withdrawal_caps: bool,
+    charge_redeem_fee: bool,
 ) -> Result<u64> {
     if collateral_amount == 0 {
         msg!("Collateral amount provided cannot be zero");
@@ -556,7 +1043,7 @@ pub fn redeem_reserve_collateral(
         return err!(LendingError::ReserveStale);
     }
 
-    let liquidity_amount = reserve.redeem_collateral(collateral_amount)?;
+    let liquidity_amount = reserve.redeem_collateral(collateral_amount, charge_redeem_fee)?;
     refresh_reserve_limit_timestamps(reserve, clock.slot)?;
     reserve.last_update.mark_stale();
 
@@ -571,6 +1058,59 @@ pub fn redeem_reserve_collateral(
     Ok(liquidity_amount)
 }
 
+pub fn compound_reserve_collateral_yield(
+    reserve: &mut Reserve,
+    clock: &Clock,
+    collateral_amount: u64,
+    principal_liquidity_amount: u64,
+    referral_fee_bps: u16,
+) -> Result<Option<CompoundYieldResult>> {
+    if collateral_amount == 0 {
+        msg!("Collateral amount provided cannot be zero");
+        return err!(LendingError::InvalidAmount);
+    }
+
+    if reserve
+        .last_update
+        .is_stale(clock.slot, PriceStatusFlags::NONE)?
+    {
+        msg!("Reserve is stale and must be refreshed in the current slot");
+        return err!(LendingError::ReserveStale);
+    }
+
+    let exchange_rate = reserve.collateral_exchange_rate()?;
+    let liquidity_value = exchange_rate.collateral_to_liquidity(collateral_amount);
+    let yield_liquidity_amount = liquidity_value.saturating_sub(principal_liquidity_amount);
+    let redeem_collateral_amount = exchange_rate.liquidity_to_collateral(yield_liquidity_amount);
+
+    if redeem_collateral_amount == 0 {
+        msg!(
+            "No yield to compound, liquidity value {} principal {}",
+            liquidity_value,
+            principal_liquidity_amount
+        );
+        return Ok(None);
+    }
+
+    let redeemed_liquidity_amount =
+        redeem_reserve_collateral(reserve, redeem_collateral_amount, clock, true, false)?;
+
+    if redeemed_liquidity_amount == 0 {
+        return Ok(None);
+    }
+
+    refresh_reserve(reserve, clock, None, referral_fee_bps)?;
+
+    let minted_collateral_amount =
+        deposit_reserve_liquidity(reserve, clock, redeemed_liquidity_amount)?;
+
+    Ok(Some(CompoundYieldResult {
+        redeemed_collateral_amount: redeem_collateral_amount,
+        yield_liquidity_amount: redeemed_liquidity_amount,
+        minted_collateral_amount,
+    }))
+}
+
 pub fn redeem_fees(reserve: &mut Reserve, slot: Slot) -> Result<u64> {
     if reserve.last_update.is_stale(slot, PriceStatusFlags::NONE)? {
         msg!(
@@ -582,14 +1122,81 @@ pub fn redeem_fees(reserve: &mut Reserve, slot: Slot) -> Result<u64> {
 
     let withdraw_amount = reserve.calculate_redeem_fees()?;
 
+    let forgive_protocol_fee_dust = reserve.config.forgive_protocol_fee_dust != 0;
+
+    if withdraw_amount == 0 && !forgive_protocol_fee_dust {
+        return err!(LendingError::InsufficientProtocolFeesToRedeem);
+    }
+
+    if withdraw_amount > 0 {
+        reserve.liquidity.redeem_fees(withdraw_amount)?;
+    }
+    if forgive_protocol_fee_dust {
+        reserve.liquidity.forgive_protocol_fees_dust();
+    }
+    reserve.last_update.mark_stale();
+
+    Ok(withdraw_amount)
+}
+
+pub fn withdraw_host_fees(reserve: &mut Reserve, slot: Slot) -> Result<u64> {
+    if reserve.last_update.is_stale(slot, PriceStatusFlags::NONE)? {
+        msg!(
+            "reserve is stale and must be refreshed in the current slot, price status: {:08b}",
+            reserve.last_update.get_price_status().0
+        );
+        return err!(LendingError::ReserveStale);
+    }
+
+    let withdraw_amount = reserve.calculate_withdraw_host_fees();
+
     if withdraw_amount == 0 {
+        return err!(LendingError::InsufficientHostFeesToWithdraw);
+    }
+
+    reserve.liquidity.withdraw_host_fees(withdraw_amount)?;
+    reserve.last_update.mark_stale();
+
+    Ok(withdraw_amount)
+}
+
+pub fn rebalance_protocol_liquidity(
+    source_reserve: &mut Reserve,
+    destination_reserve: &mut Reserve,
+    amount: u64,
+    slot: Slot,
+) -> Result<u64> {
+    if source_reserve
+        .last_update
+        .is_stale(slot, PriceStatusFlags::NONE)?
+        || destination_reserve
+            .last_update
+            .is_stale(slot, PriceStatusFlags::NONE)?
+    {
+        msg!("Reserves are stale and must be refreshed in the current slot");
+        return err!(LendingError::ReserveStale);
+    }
+
+    if source_reserve.liquidity.mint_pubkey != destination_reserve.liquidity.mint_pubkey {
+        msg!("Source and destination reserves must have the same liquidity mint");
+        return err!(LendingError::InvalidAccountInput);
+    }
+
+    let rebalance_amount = min(amount, source_reserve.calculate_redeem_fees()?);
+
+    if rebalance_amount == 0 {
         return err!(LendingError::InsufficientProtocolFeesToRedeem);
     }
 
-    reserve.liquidity.redeem_fees(withdraw_amount)?;
-    reserve.last_update.mark_stale();
+    source_reserve.liquidity.redeem_fees(rebalance_amount)?;
+    destination_reserve
+        .liquidity
+        .add_protocol_liquidity(rebalance_amount)?;
 
-    Ok(withdraw_amount)
+    source_reserve.last_update.mark_stale();
+    destination_reserve.last_update.mark_stale();
+
+    Ok(rebalance_amount)
 }
 
 pub fn repay_obligation_liquidity<'info, T>(
@@ -613,8 +1220,10 @@ where
         .last_update
         .is_stale(clock.slot, PriceStatusFlags::NONE)?
     {
-        msg!("Repay reserve is stale and must be refreshed in the current slot");
-        return err!(LendingError::ReserveStale);
+        msg!(
+            "Repay reserve is stale, accruing interest up to the current slot without a new price"
+        );
+        refresh_reserve(repay_reserve, clock, None, lending_market.referral_fee_bps)?;
     }
 
     let (liquidity, liquidity_index) =
@@ -673,6 +1282,352 @@ where
     Ok(repay_amount)
 }
 
+pub fn calculate_repay_amount_to_target_ltv(
+    repay_reserve: &Reserve,
+    obligation: &Obligation,
+    slot: Slot,
+    repay_reserve_pk: Pubkey,
+    target_ltv_pct: u8,
+) -> Result<u64> {
+    if target_ltv_pct >= 100 {
+        msg!("Target LTV must be below 100%");
+        return err!(LendingError::InvalidConfig);
+    }
+
+    if repay_reserve
+        .last_update
+        .is_stale(slot, PriceStatusFlags::ALL_CHECKS)?
+    {
+        msg!("Repay reserve is stale and must be refreshed in the current slot");
+        return err!(LendingError::ReserveStale);
+    }
+
+    check_obligation_fully_refreshed_and_not_null(obligation, slot)?;
+
+    let (liquidity, _) = obligation.find_liquidity_in_borrows(repay_reserve_pk)?;
+    let borrowed_amount_f = Fraction::from_bits(liquidity.borrowed_amount_sf);
+
+    let current_bf_debt_value =
+        Fraction::from_bits(obligation.borrow_factor_adjusted_debt_value_sf);
+    let target_bf_debt_value =
+        Fraction::from_bits(obligation.deposited_value_sf) * Fraction::from_percent(target_ltv_pct);
+
+    if current_bf_debt_value <= target_bf_debt_value {
+        return Ok(0);
+    }
+
+    let mint_decimal_factor: u128 =
+        ten_pow(repay_reserve.liquidity.mint_decimals.try_into().unwrap()).into();
+    let bf_adjusted_price = repay_reserve.liquidity.get_market_price_f()
+        * repay_reserve.borrow_factor_f(obligation.elevation_group != ELEVATION_GROUP_NONE);
+
+    let repay_amount_f =
+        (current_bf_debt_value - target_bf_debt_value) * mint_decimal_factor / bf_adjusted_price;
+
+    Ok(min(repay_amount_f, borrowed_amount_f).to_ceil())
+}
+
+pub fn calculate_min_collateral_deposit_for_borrow(
+    lending_market: &LendingMarket,
+    collateral_reserve: &Reserve,
+    borrow_reserve: &Reserve,
+    obligation: &Obligation,
+    borrow_amount: u64,
+    slot: Slot,
+) -> Result<u64> {
+    if collateral_reserve
+        .last_update
+        .is_stale(slot, PriceStatusFlags::ALL_CHECKS)?
+        || borrow_reserve
+            .last_update
+            .is_stale(slot, PriceStatusFlags::ALL_CHECKS)?
+    {
+        msg!("Reserves are stale and must be refreshed in the current slot");
+        return err!(LendingError::ReserveStale);
+    }
+
+    if obligation
+        .last_update
+        .is_stale(slot, PriceStatusFlags::ALL_CHECKS)?
+    {
+        msg!("Obligation is stale and must be refreshed in the current slot");
+        return err!(LendingError::ObligationStale);
+    }
+
+    let elevation_group = get_elevation_group(obligation.elevation_group, lending_market)?;
+
+    let borrow_amount_f = Fraction::from(borrow_amount);
+    let (borrow_fee, referrer_fee) = borrow_reserve.config.fees.calculate_borrow_fees(
+        borrow_amount_f,
+        FeeCalculation::Exclusive,
+        lending_market.referral_fee_bps,
+        obligation.has_referrer(),
+        borrow_reserve
+            .config
+            .get_borrow_fee_discount_bps(elevation_group.is_some()),
+    )?;
+    let borrow_amount_f = borrow_amount_f + Fraction::from_num(borrow_fee + referrer_fee);
+
+    let new_borrow_factor_adjusted_debt_value =
+        Fraction::from_bits(obligation.borrow_factor_adjusted_debt_value_sf)
+            + utils::calculate_market_value_from_liquidity_amount(borrow_reserve, borrow_amount_f)?
+                * borrow_reserve.borrow_factor_f(elevation_group.is_some());
+    let allowed_borrow_value = Fraction::from_bits(obligation.allowed_borrow_value_sf);
+
+    if new_borrow_factor_adjusted_debt_value <= allowed_borrow_value {
+        return Ok(0);
+    }
+
+    let (coll_ltv_pct, _) =
+        get_max_ltv_and_liquidation_threshold(collateral_reserve, elevation_group)?;
+    let borrow_collateral_haircut =
+        Fraction::from_bps(collateral_reserve.config.borrow_collateral_haircut_bps);
+    let collateral_borrow_power =
+        (Fraction::ONE - borrow_collateral_haircut) * Fraction::from_percent(coll_ltv_pct);
+
+    if collateral_borrow_power == Fraction::ZERO {
+        msg!("Collateral reserve does not provide any borrowing power");
+        return err!(LendingError::BorrowTooLarge);
+    }
+
+    let required_collateral_value =
+        (new_borrow_factor_adjusted_debt_value - allowed_borrow_value) / collateral_borrow_power;
+
+    let mint_decimal_factor: u128 = ten_pow(
+        collateral_reserve
+            .liquidity
+            .mint_decimals
+            .try_into()
+            .unwrap(),
+    )
+    .into();
+    let required_liquidity_amount_f = required_collateral_value * mint_decimal_factor
+        / collateral_reserve.liquidity.get_market_price_f();
+
+    Ok(required_liquidity_amount_f.to_ceil())
+}
+
+pub fn estimate_slots_to_liquidation(
+    debt_reserve: &Reserve,
+    obligation: &Obligation,
+    slot: Slot,
+    debt_reserve_pk: Pubkey,
+) -> Result<Option<u64>> {
+    const MAX_PROJECTED_SLOTS: u64 = SLOTS_PER_YEAR * 10;
+
+    check_obligation_fully_refreshed_and_not_null(obligation, slot)?;
+
+    let bf_debt_value = Fraction::from_bits(obligation.borrow_factor_adjusted_debt_value_sf);
+    let unhealthy_borrow_value = Fraction::from_bits(obligation.unhealthy_borrow_value_sf);
+
+    if bf_debt_value == Fraction::ZERO || bf_debt_value >= unhealthy_borrow_value {
+        return Ok(None);
+    }
+
+    let (liquidity, _) = obligation.find_liquidity_in_borrows(debt_reserve_pk)?;
+    let position_bf_debt_value =
+        Fraction::from_bits(liquidity.borrow_factor_adjusted_market_value_sf);
+    let other_bf_debt_value = bf_debt_value.saturating_sub(position_bf_debt_value);
+
+    let borrow_rate = debt_reserve.current_borrow_rate()?;
+
+    if position_bf_debt_value == Fraction::ZERO || borrow_rate == Fraction::ZERO {
+        return Ok(None);
+    }
+
+    let is_unhealthy_after = |elapsed_slots: u64| {
+        other_bf_debt_value
+            + position_bf_debt_value * approximate_compounded_interest(borrow_rate, elapsed_slots)
+            >= unhealthy_borrow_value
+    };
+
+    if !is_unhealthy_after(MAX_PROJECTED_SLOTS) {
+        return Ok(None);
+    }
+
+    let (mut low, mut high) = (0, MAX_PROJECTED_SLOTS);
+    while low + 1 < high {
+        let mid = low + (high - low) / 2;
+        if is_unhealthy_after(mid) {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+
+    Ok(Some(high))
+}
+
+/// Supply vault balance against the liquidity the reserve accounts for in it. The protocol fees
+/// not yet redeemed are part of the available amount, a positive drift is surplus in the vault.
+pub fn get_reserve_vault_balance_snapshot(
+    reserve: &Reserve,
+    vault_balance: u64,
+) -> Result<ReserveVaultBalanceSnapshot> {
+    let available_amount = reserve.liquidity.available_amount;
+    let drift = i64::try_from(i128::from(vault_balance) - i128::from(available_amount))
+        .map_err(|_| error!(LendingError::MathOverflow))?;
+
+    Ok(ReserveVaultBalanceSnapshot {
+        vault_balance,
+        available_amount,
+        accumulated_protocol_fees: Fraction::from_bits(
+            reserve.liquidity.accumulated_protocol_fees_sf,
+        )
+        .to_floor(),
+        drift,
+    })
+}
+
+pub fn get_obligation_snapshot(obligation: &Obligation, slot: Slot) -> Result<ObligationSnapshot> {
+    if obligation
+        .last_update
+        .is_stale(slot, PriceStatusFlags::ALL_CHECKS)?
+    {
+        msg!(
+            "Obligation is stale and must be refreshed in the current slot, price status: {:08b}",
+            obligation.last_update.get_price_status().0
+        );
+        return err!(LendingError::ObligationStale);
+    }
+
+    let loan_to_value = if obligation.deposited_value_sf == 0 {
+        Fraction::ZERO
+    } else {
+        obligation.loan_to_value()
+    };
+
+    Ok(ObligationSnapshot {
+        deposited_value_sf: obligation.deposited_value_sf,
+        borrow_factor_adjusted_debt_value_sf: obligation.borrow_factor_adjusted_debt_value_sf,
+        loan_to_value_sf: loan_to_value.to_bits(),
+        elevation_group: obligation.elevation_group,
+        has_debt: obligation.has_debt != 0,
+    })
+}
+
+pub fn calculate_market_totals<'a>(
+    reserves: impl Iterator<Item = &'a Reserve>,
+) -> Result<MarketTotals> {
+    let mut total_supply_value_f = Fraction::ZERO;
+    let mut total_borrow_value_f = Fraction::ZERO;
+
+    for reserve in reserves {
+        total_supply_value_f += utils::calculate_market_value_from_liquidity_amount(
+            reserve,
+            reserve.liquidity.total_supply()?,
+        )?;
+        total_borrow_value_f += utils::calculate_market_value_from_liquidity_amount(
+            reserve,
+            reserve.liquidity.total_borrow(),
+        )?;
+    }
+
+    let utilization_rate_f = if total_supply_value_f == Fraction::ZERO {
+        Fraction::ZERO
+    } else {
+        min(total_borrow_value_f / total_supply_value_f, Fraction::ONE)
+    };
+
+    Ok(MarketTotals {
+        total_supply_value_f,
+        total_borrow_value_f,
+        utilization_rate_f,
+    })
+}
+
+pub fn get_reserve_ltv_and_liquidation_threshold_in_elevation_group(
+    reserve: &Reserve,
+    reserve_pk: Pubkey,
+    elevation_group_id: u8,
+    lending_market: &LendingMarket,
+) -> Result<(u8, u8)> {
+    let elevation_group = get_elevation_group(elevation_group_id, lending_market)?;
+
+    if let Some(elevation_group) = elevation_group {
+        require!(
+            reserve
+                .config
+                .elevation_groups
+                .contains(&elevation_group.id),
+            LendingError::InconsistentElevationGroup
+        );
+        require_keys_neq!(
+            reserve_pk,
+            elevation_group.debt_reserve,
+            LendingError::ElevationGroupDebtReserveAsCollateral
+        );
+    }
+
+    get_max_ltv_and_liquidation_threshold(reserve, elevation_group)
+}
+
+pub fn update_obligation_collateral_supply_only(
+    lending_market: &LendingMarket,
+    deposit_reserve: &Reserve,
+    obligation: &mut Obligation,
+    deposit_reserve_pk: Pubkey,
+    supply_only: bool,
+    slot: Slot,
+) -> Result<()> {
+    let collateral_index = obligation.position_of_collateral_in_deposits(deposit_reserve_pk)?;
+    let collateral = &obligation.deposits[collateral_index];
+
+    if collateral.is_supply_only() == supply_only {
+        msg!("Deposit supply-only flag is already {}", supply_only);
+        return Ok(());
+    }
+
+    if supply_only && !obligation.borrows_empty() {
+        check_obligation_fully_refreshed_and_not_null(obligation, slot)?;
+
+        let (coll_ltv_pct, _) = get_max_ltv_and_liquidation_threshold(
+            deposit_reserve,
+            get_elevation_group(obligation.elevation_group, lending_market)?,
+        )?;
+        let borrow_collateral_haircut =
+            Fraction::from_bps(deposit_reserve.config.borrow_collateral_haircut_bps);
+        let collateral_borrow_value = Fraction::from_bits(collateral.market_value_sf)
+            * (Fraction::ONE - borrow_collateral_haircut)
+            * Fraction::from_percent(coll_ltv_pct);
+        let new_allowed_borrow_value = Fraction::from_bits(obligation.allowed_borrow_value_sf)
+            .saturating_sub(collateral_borrow_value);
+
+        if new_allowed_borrow_value
+            < Fraction::from_bits(obligation.borrow_factor_adjusted_debt_value_sf)
+        {
+            msg!(
+                "Excluding the deposit from collateral would leave the obligation with allowed borrow value {} below its debt {}",
+                new_allowed_borrow_value.to_display(),
+                Fraction::from_bits(obligation.borrow_factor_adjusted_debt_value_sf).to_display()
+            );
+            return err!(LendingError::WorseLTVBlocked);
+        }
+    }
+
+    obligation.deposits[collateral_index].supply_only = supply_only as u8;
+    obligation.last_update.mark_stale();
+
+    Ok(())
+}
+
+/// Protected collaterals are liquidated after the other collaterals sharing the lowest
+/// liquidation LTV. The mandatory lowest liquidation LTV priority still applies first, so a
+/// protected collateral with a strictly lower liquidation LTV is liquidated before any other.
+/// Auto-deleveraging ignores the flag.
+pub fn update_obligation_collateral_protected(
+    obligation: &mut Obligation,
+    deposit_reserve_pk: Pubkey,
+    protected: bool,
+) -> Result<()> {
+    let collateral_index = obligation.position_of_collateral_in_deposits(deposit_reserve_pk)?;
+
+    obligation.deposits[collateral_index].protected = protected as u8;
+    obligation.last_update.mark_stale();
+
+    Ok(())
+}
+
 pub fn request_elevation_group<'info, T, U>(
     obligation: &mut Obligation,
     lending_market: &LendingMarket,
@@ -861,13 +1816,17 @@ where
     T: AnyAccountLoader<'info, Reserve>,
 {
     let mut lowest_deposit_liquidation_ltv_threshold = u8::MAX;
+    let mut lowest_unprotected_deposit_liquidation_ltv_threshold = u8::MAX;
     let mut deposited_value = Fraction::ZERO;
     let mut allowed_borrow_value = Fraction::ZERO;
     let mut unhealthy_borrow_value = Fraction::ZERO;
     let mut num_of_obsolete_reserves = 0;
     let mut prices_state = PriceStatusFlags::all();
     let mut borrowing_disabled = false;
+    let mut has_zero_ltv_deposit = false;
     let mut collaterals_count = 0;
+    let mut price_timestamps = PriceTimestampsRange::default();
+    let is_supply_only_counted_as_collateral = obligation.is_supply_only_counted_as_collateral();
 
     let elevation_group_and_borrowed_amount: Option<(&ElevationGroup, u64)> = match (
         elevation_group,
@@ -947,24 +1906,40 @@ where
             calculate_obligation_collateral_market_value(&deposit_reserve, deposit)?;
         deposit.market_value_sf = market_value_f.to_bits();
 
-        let (coll_ltv_pct, coll_liquidation_threshold_pct) =
-            get_max_ltv_and_liquidation_threshold(&deposit_reserve, elevation_group)?;
+        if !deposit.is_supply_only() || is_supply_only_counted_as_collateral {
+            let (coll_ltv_pct, coll_liquidation_threshold_pct) =
+                get_max_ltv_and_liquidation_threshold(&deposit_reserve, elevation_group)?;
 
-        if market_value_f >= lending_market.min_value_skip_liquidation_ltv_bf_checks
-            && coll_liquidation_threshold_pct > 0
-        {
-            lowest_deposit_liquidation_ltv_threshold =
-                lowest_deposit_liquidation_ltv_threshold.min(coll_liquidation_threshold_pct);
-        }
+            if market_value_f >= lending_market.min_value_skip_liquidation_ltv_bf_checks
+                && coll_liquidation_threshold_pct > 0
+            {
+                lowest_deposit_liquidation_ltv_threshold =
+                    lowest_deposit_liquidation_ltv_threshold.min(coll_liquidation_threshold_pct);
+                if !deposit.is_protected() {
+                    lowest_unprotected_deposit_liquidation_ltv_threshold =
+                        lowest_unprotected_deposit_liquidation_ltv_threshold
+                            .min(coll_liquidation_threshold_pct);
+                }
+            }
+
+            if coll_ltv_pct == 0 && deposit.deposited_amount > 0 {
+                has_zero_ltv_deposit = true;
+            }
 
-        deposited_value = deposited_value.add(market_value_f);
-        allowed_borrow_value += market_value_f * Fraction::from_percent(coll_ltv_pct);
-        unhealthy_borrow_value +=
-            market_value_f * Fraction::from_percent(coll_liquidation_threshold_pct);
+            deposited_value = deposited_value.add(market_value_f);
+            let borrow_collateral_haircut =
+                Fraction::from_bps(deposit_reserve.config.borrow_collateral_haircut_bps);
+            allowed_borrow_value += market_value_f
+                * (Fraction::ONE - borrow_collateral_haircut)
+                * Fraction::from_percent(coll_ltv_pct);
+            unhealthy_borrow_value +=
+                market_value_f * Fraction::from_percent(coll_liquidation_threshold_pct);
+        }
 
         obligation.deposits_asset_tiers[index] = deposit_reserve.config.asset_tier;
 
         prices_state &= deposit_reserve.last_update.get_price_status();
+        price_timestamps.include(deposit_reserve.liquidity.market_price_last_updated_ts);
 
         xmsg!(
             "Deposit: {} amount: {} value: {}",
@@ -993,6 +1968,12 @@ where
         unhealthy_borrow_value_f: unhealthy_borrow_value,
         prices_state,
         borrowing_disabled,
+        has_zero_ltv_deposit,
+        has_unprotected_lowest_liquidation_ltv_deposit: lowest_deposit_liquidation_ltv_threshold
+            != u8::MAX
+            && lowest_unprotected_deposit_liquidation_ltv_threshold
+                == lowest_deposit_liquidation_ltv_threshold,
+        price_timestamps,
     })
 }
 
@@ -1014,8 +1995,10 @@ where
     let mut highest_borrow_factor_f = Fraction::ONE;
 
     let obligation_has_referrer = obligation.has_referrer();
+    let borrow_factor_multiplier = obligation.borrow_factor_multiplier();
     let mut borrowed_amounts_accumulator_for_elevation_group = 0_u64;
     let mut num_borrow_reserves = 0;
+    let mut price_timestamps = PriceTimestampsRange::default();
 
     for (index, borrow) in obligation
         .borrows
@@ -1099,7 +2082,11 @@ where
             highest_borrow_factor_f = highest_borrow_factor_f.max(borrow_factor_f);
         }
 
-        let borrow_factor_adjusted_market_value: Fraction = market_value_f * borrow_factor_f;
+        let obligation_borrow_factor_f = borrow_factor_multiplier.map_or(borrow_factor_f, |m| {
+            (borrow_factor_f * m).max(Fraction::ONE)
+        });
+        let borrow_factor_adjusted_market_value: Fraction =
+            market_value_f * obligation_borrow_factor_f;
         borrow.borrow_factor_adjusted_market_value_sf =
             borrow_factor_adjusted_market_value.to_bits();
 
@@ -1110,6 +2097,7 @@ where
         obligation.has_debt = 1;
 
         prices_state &= borrow_reserve.last_update.get_price_status();
+        price_timestamps.include(borrow_reserve.liquidity.market_price_last_updated_ts);
 
         xmsg!(
             "Borrow: {} amount: {} value: {} value_bf: {}",
@@ -1136,6 +2124,7 @@ where
         borrowed_amount_in_elevation_group,
         prices_state,
         highest_borrow_factor_pct: highest_borrow_factor_f.to_percent::<u64>().unwrap(),
+        price_timestamps,
     })
 }
 
@@ -1159,6 +2148,7 @@ where
         prices_state: borrows_prices_state,
         borrowed_amount_in_elevation_group,
         highest_borrow_factor_pct,
+        price_timestamps: borrows_price_timestamps,
     } = refresh_obligation_borrows(
         obligation,
         lending_market,
@@ -1176,6 +2166,9 @@ where
         unhealthy_borrow_value_f: unhealthy_borrow_value,
         prices_state: deposits_prices_state,
         borrowing_disabled,
+        has_zero_ltv_deposit,
+        has_unprotected_lowest_liquidation_ltv_deposit,
+        price_timestamps: deposits_price_timestamps,
     } = refresh_obligation_deposits(
         obligation,
         lending_market,
@@ -1185,6 +2178,37 @@ where
         borrowed_amount_in_elevation_group,
     )?;
 
+    let mut prices_state = deposits_prices_state.intersection(borrows_prices_state);
+
+    if lending_market.max_price_age_divergence_secs > 0 {
+        let price_age_divergence_secs = deposits_price_timestamps
+            .merge(borrows_price_timestamps)
+            .divergence_secs();
+        if price_age_divergence_secs > lending_market.max_price_age_divergence_secs {
+            msg!(
+                "Price age divergence between obligation reserves is too large: {}s > {}s, borrows and withdrawals blocked",
+                price_age_divergence_secs,
+                lending_market.max_price_age_divergence_secs
+            );
+            // Not part of the liquidation checks, so liquidations stay possible
+            prices_state.remove(PriceStatusFlags::TWAP_AGE_CHECKED);
+        }
+    }
+
+    if lending_market.is_obligation_value_drift_check_enabled()
+        && is_obligation_value_drifted(
+            obligation,
+            lending_market,
+            slot,
+            deposited_value_f,
+            borrow_factor_adjusted_debt_value_f,
+        )?
+        && lending_market.is_obligation_value_drift_check_strict()
+    {
+        // Not part of the liquidation checks, so liquidations stay possible
+        prices_state.remove(PriceStatusFlags::HEURISTIC_CHECKED);
+    }
+
     obligation.borrowed_assets_market_value_sf = borrowed_assets_market_value_f.to_bits();
 
     obligation.deposited_value_sf = deposited_value_f.to_bits();
@@ -1209,24 +2233,89 @@ where
     obligation.num_of_obsolete_reserves = num_of_obsolete_reserves;
 
     obligation.borrowing_disabled = borrowing_disabled.into();
+    obligation.has_zero_ltv_deposit = has_zero_ltv_deposit.into();
+    obligation.has_unprotected_lowest_liquidation_ltv_deposit =
+        has_unprotected_lowest_liquidation_ltv_deposit.into();
     obligation.highest_borrow_factor_pct = highest_borrow_factor_pct;
 
-    let prices_state = deposits_prices_state.intersection(borrows_prices_state);
     obligation.last_update.update_slot(slot, Some(prices_state));
 
     Ok(())
 }
 
+/// Compares freshly computed values against the ones stored by the previous refresh and emits an
+/// event when they drifted. Skipped when an operation touched the obligation since (it is then
+/// marked stale and amounts have changed).
+fn is_obligation_value_drifted(
+    obligation: &Obligation,
+    lending_market: &LendingMarket,
+    slot: Slot,
+    deposited_value_f: Fraction,
+    borrow_factor_adjusted_debt_value_f: Fraction,
+) -> Result<bool> {
+    if obligation.last_update.is_marked_stale() {
+        return Ok(false);
+    }
+
+    let slots_elapsed = obligation.last_update.slots_elapsed(slot)?;
+    let tolerance_f = Fraction::from_bps(lending_market.obligation_value_drift_tolerance_bps)
+        .saturating_mul_int(u128::from(slots_elapsed.max(1)));
+
+    let previous_deposited_value_f = Fraction::from_bits(obligation.deposited_value_sf);
+    let previous_debt_value_f =
+        Fraction::from_bits(obligation.borrow_factor_adjusted_debt_value_sf);
+
+    let drifted = |previous: Fraction, current: Fraction| {
+        let diff = if current > previous {
+            current - previous
+        } else {
+            previous - current
+        };
+        diff > previous.saturating_mul(tolerance_f)
+    };
+
+    if !drifted(previous_deposited_value_f, deposited_value_f)
+        && !drifted(previous_debt_value_f, borrow_factor_adjusted_debt_value_f)
+    {
+        return Ok(false);
+    }
+
+    msg!(
+        "Obligation values drifted over {} slots: deposited {} -> {}, debt {} -> {}",
+        slots_elapsed,
+        previous_deposited_value_f.to_display(),
+        deposited_value_f.to_display(),
+        previous_debt_value_f.to_display(),
+        borrow_factor_adjusted_debt_value_f.to_display()
+    );
+
+    emit!(ObligationValueDrift {
+        lending_market: obligation.lending_market,
+        owner: obligation.owner,
+        slot,
+        slots_elapsed,
+        previous_deposited_value_sf: obligation.deposited_value_sf,
+        deposited_value_sf: deposited_value_f.to_bits(),
+        previous_borrow_factor_adjusted_debt_value_sf: obligation
+            .borrow_factor_adjusted_debt_value_sf,
+        borrow_factor_adjusted_debt_value_sf: borrow_factor_adjusted_debt_value_f.to_bits(),
+    });
+
+    Ok(true)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn liquidate_and_redeem<'info, T>(
     lending_market: &LendingMarket,
     repay_reserve: &dyn AnyAccountLoader<Reserve>,
     withdraw_reserve: &dyn AnyAccountLoader<Reserve>,
     obligation: &mut Obligation,
+    liquidator: &Pubkey,
     clock: &Clock,
     liquidity_amount: u64,
     min_acceptable_received_liquidity_amount: u64,
     max_allowed_ltv_override_pct_opt: Option<u64>,
+    cap_to_redeemable_collateral: bool,
     deposit_reserves_iter: impl Iterator<Item = T>,
 ) -> Result<LiquidateAndRedeemResult>
 where
@@ -1237,15 +2326,18 @@ where
         withdraw_collateral_amount,
         withdraw_amount,
         liquidation_bonus_rate,
+        liquidation_reason,
         ..
     } = liquidate_obligation(
         lending_market,
         repay_reserve,
         withdraw_reserve,
         obligation,
+        liquidator,
         clock,
         liquidity_amount,
         max_allowed_ltv_override_pct_opt,
+        cap_to_redeemable_collateral,
         deposit_reserves_iter,
     )?;
 
@@ -1266,6 +2358,7 @@ where
         withdraw_amount,
         total_withdraw_liquidity_amount,
         withdraw_collateral_amount,
+        liquidation_reason,
     })
 }
 
@@ -1275,9 +2368,11 @@ pub fn liquidate_obligation<'info, T>(
     repay_reserve: &dyn AnyAccountLoader<Reserve>,
     withdraw_reserve: &dyn AnyAccountLoader<Reserve>,
     obligation: &mut Obligation,
+    liquidator: &Pubkey,
     clock: &Clock,
     liquidity_amount: u64,
     max_allowed_ltv_override_pct_opt: Option<u64>,
+    cap_to_redeemable_collateral: bool,
     deposit_reserves_iter: impl Iterator<Item = T>,
 ) -> Result<LiquidateObligationResult>
 where
@@ -1291,6 +2386,16 @@ where
     let repay_reserve_ref = repay_reserve.get()?;
     let withdraw_reserve_ref = withdraw_reserve.get()?;
 
+    if !repay_reserve_ref.is_liquidator_allowed(liquidator)
+        || !withdraw_reserve_ref.is_liquidator_allowed(liquidator)
+    {
+        msg!(
+            "Liquidator {} is not in the reserve liquidator allowlist",
+            liquidator
+        );
+        return err!(LendingError::LiquidatorNotAllowed);
+    }
+
     let slot = clock.slot;
 
     let elevation_group = get_elevation_group(obligation.elevation_group, lending_market)?;
@@ -1327,17 +2432,45 @@ where
         return err!(LendingError::ObligationCollateralEmpty);
     }
 
+    if !obligation.is_counted_as_collateral(collateral) {
+        xmsg!("Supply-only deposit is not collateral while other collateral is left and can't be used for liquidation");
+        return err!(LendingError::CollateralNonLiquidatable);
+    }
+
     let is_debt_reserve_highest_borrow_factor =
         repay_reserve_ref.config.borrow_factor_pct >= obligation.highest_borrow_factor_pct;
 
-    let is_collateral_reserve_lowest_liquidation_ltv = collateral_liquidation_threshold_pct as u64
-        <= obligation.lowest_reserve_deposit_liquidation_ltv;
+    let is_collateral_reserve_liquidation_priority = if lending_market
+        .is_liquidation_collateral_priority_highest_value()
+    {
+        obligation
+            .deposits
+            .iter()
+            .filter(|c| {
+                c.deposit_reserve != Pubkey::default() && obligation.is_counted_as_collateral(c)
+            })
+            .all(|c| c.market_value_sf <= collateral.market_value_sf)
+    } else {
+        let mut is_collateral_reserve_lowest_liquidation_ltv = collateral_liquidation_threshold_pct
+            as u64
+            <= obligation.lowest_reserve_deposit_liquidation_ltv;
+
+        if is_collateral_reserve_lowest_liquidation_ltv
+            && collateral.is_protected()
+            && obligation.has_unprotected_lowest_liquidation_ltv_deposit()
+        {
+            xmsg!("Collateral is protected and an unprotected collateral with the same liquidation LTV must be liquidated first");
+            is_collateral_reserve_lowest_liquidation_ltv = false;
+        }
+        is_collateral_reserve_lowest_liquidation_ltv
+    };
 
     let CalculateLiquidationResult {
         settle_amount_f: settle_amount,
         repay_amount,
         withdraw_amount,
         liquidation_bonus_rate,
+        liquidation_reason,
     } = liquidation_operations::calculate_liquidation(
         &withdraw_reserve_ref,
         &repay_reserve_ref,
@@ -1348,10 +2481,43 @@ where
         collateral,
         slot,
         is_debt_reserve_highest_borrow_factor,
-        is_collateral_reserve_lowest_liquidation_ltv,
+        is_collateral_reserve_liquidation_priority,
         max_allowed_ltv_override_pct_opt,
     )?;
 
+    let uncapped_withdraw_amount = withdraw_amount;
+    let (settle_amount, repay_amount, withdraw_amount) = if cap_to_redeemable_collateral
+        && repay_reserve.get_pubkey() != withdraw_reserve.get_pubkey()
+    {
+        let max_redeemable_collateral = withdraw_reserve_ref
+            .collateral_exchange_rate()?
+            .liquidity_to_collateral(withdraw_reserve_ref.liquidity.available_amount);
+        cap_liquidation_to_redeemable_collateral(
+            settle_amount,
+            repay_amount,
+            withdraw_amount,
+            max_redeemable_collateral,
+        )?
+    } else {
+        (settle_amount, repay_amount, withdraw_amount)
+    };
+    // The rest of a liquidation capped by the vault liquidity must be possible as soon as
+    // liquidity comes back
+    let is_capped_to_redeemable_collateral = withdraw_amount < uncapped_withdraw_amount;
+
+    let is_full_liquidation = settle_amount >= Fraction::from_bits(liquidity.borrowed_amount_sf);
+    if !is_full_liquidation
+        && !is_capped_to_redeemable_collateral
+        && liquidation_operations::is_liquidation_cooldown_active(lending_market, obligation, slot)
+    {
+        msg!(
+            "Obligation was liquidated at slot {}, next partial liquidation allowed {} slots later",
+            obligation.last_liquidation_slot,
+            lending_market.min_slots_between_liquidations
+        );
+        return err!(LendingError::LiquidationCooldownActive);
+    }
+
     let is_full_withdrawal = collateral.deposited_amount == withdraw_amount;
 
     drop(repay_reserve_ref);
@@ -1406,13 +2572,52 @@ where
         )?;
     }
 
+    obligation.last_liquidation_slot = slot;
+
     Ok(LiquidateObligationResult {
         settle_amount_f: settle_amount,
         repay_amount,
         withdraw_amount,
-        withdraw_collateral_amount,
-        liquidation_bonus_rate,
-    })
+        withdraw_collateral_amount,
+        liquidation_bonus_rate,
+        liquidation_reason,
+    })
+}
+
+/// Scales the liquidation down so that all the withdrawn collateral can be redeemed, the repaid
+/// debt being reduced in the same proportion to keep the liquidation bonus unchanged.
+fn cap_liquidation_to_redeemable_collateral(
+    settle_amount: Fraction,
+    repay_amount: u64,
+    withdraw_amount: u64,
+    max_redeemable_collateral: u64,
+) -> Result<(Fraction, u64, u64)> {
+    if withdraw_amount <= max_redeemable_collateral {
+        return Ok((settle_amount, repay_amount, withdraw_amount));
+    }
+
+    if max_redeemable_collateral == 0 {
+        msg!("No collateral can be redeemed from the withdraw reserve");
+        return err!(LendingError::InsufficientLiquidity);
+    }
+
+    let ratio = Fraction::from(max_redeemable_collateral) / u128::from(withdraw_amount);
+    let capped_settle_amount = settle_amount * ratio;
+    let capped_repay_amount: u64 = capped_settle_amount.to_ceil();
+
+    xmsg!(
+        "Liquidation capped to redeemable collateral: withdraw {} -> {}, repay {} -> {}",
+        withdraw_amount,
+        max_redeemable_collateral,
+        repay_amount,
+        capped_repay_amount
+    );
+
+    Ok((
+        capped_settle_amount,
+        capped_repay_amount,
+        max_redeemable_collateral,
+    ))
 }
 
 pub(crate) fn post_liquidate_redeem(
@@ -1425,8 +2630,13 @@ pub(crate) fn post_liquidate_redeem(
     clock: &Clock,
 ) -> Result<Option<(u64, u64)>> {
     if withdraw_collateral_amount != 0 {
-        let withdraw_liquidity_amount =
-            redeem_reserve_collateral(withdraw_reserve, withdraw_collateral_amount, clock, false)?;
+        let withdraw_liquidity_amount = redeem_reserve_collateral(
+            withdraw_reserve,
+            withdraw_collateral_amount,
+            clock,
+            false,
+            false,
+        )?;
         let protocol_fee = liquidation_operations::calculate_protocol_liquidation_fee(
             withdraw_liquidity_amount,
             liquidation_bonus_rate,
@@ -1570,12 +2780,26 @@ where
     let borrowed_amount_f = Fraction::from_bits(liquidity.borrowed_amount_sf);
     let forgive_amount_f = min(liquidity_amount_f, borrowed_amount_f);
 
-    if forgive_amount_f >= reserve.liquidity.total_supply().unwrap() {
-        msg!("Reserve becomes deprecated");
+    if forgive_amount_f == Fraction::ZERO {
+        msg!("Debt amount to forgive cannot be zero");
+        return err!(LendingError::InvalidAmount);
+    }
+
+    let total_supply_f = reserve.liquidity.total_supply()?;
+    if forgive_amount_f >= total_supply_f {
+        msg!(
+            "Forgiven debt {} wipes out the reserve total supply {}, reserve becomes deprecated",
+            forgive_amount_f,
+            total_supply_f
+        );
         reserve.version = u64::MAX;
     }
 
-    msg!("Forgiving debt amount {}", forgive_amount_f);
+    msg!(
+        "Forgiving debt amount {}, remaining debt {}",
+        forgive_amount_f,
+        borrowed_amount_f - forgive_amount_f
+    );
 
     utils::update_elevation_group_debt_trackers_on_repay(
         forgive_amount_f.to_ceil(),
@@ -1595,11 +2819,173 @@ where
     Ok(forgive_amount_f)
 }
 
+/// Reactivating a deprecated reserve requires it to be solvent: its available and borrowed
+/// liquidity must cover the accumulated and pending fees, and strictly exceed them while
+/// collateral is outstanding, so that the collateral exchange rate stays positive.
+pub fn update_reserve_deprecation(reserve: &mut Reserve, deprecate: bool) -> Result<()> {
+    if deprecate {
+        if reserve.version != PROGRAM_VERSION as u64 {
+            msg!(
+                "Reserve version {} is not the current program version",
+                reserve.version
+            );
+            return err!(LendingError::ReserveDeprecated);
+        }
+        reserve.version = u64::MAX;
+        return Ok(());
+    }
+
+    if reserve.version != u64::MAX {
+        msg!("Reserve version {} is not deprecated", reserve.version);
+        return err!(LendingError::InvalidConfig);
+    }
+
+    let total_assets = Fraction::from(reserve.liquidity.available_amount)
+        + Fraction::from_bits(reserve.liquidity.borrowed_amount_sf);
+    let total_fees = Fraction::from_bits(reserve.liquidity.accumulated_protocol_fees_sf)
+        + Fraction::from_bits(reserve.liquidity.accumulated_referrer_fees_sf)
+        + Fraction::from_bits(reserve.liquidity.pending_referrer_fees_sf)
+        + Fraction::from_bits(reserve.liquidity.pending_supply_referrer_fees_sf);
+    let is_solvent = if reserve.collateral.mint_total_supply > 0 {
+        total_assets > total_fees
+    } else {
+        total_assets >= total_fees
+    };
+
+    if !is_solvent {
+        msg!(
+            "Reserve assets {} do not cover its fees {} and outstanding collateral",
+            total_assets.to_display(),
+            total_fees.to_display()
+        );
+        return err!(LendingError::ReserveInsolvent);
+    }
+
+    reserve.version = PROGRAM_VERSION as u64;
+    reserve.last_update.mark_stale();
+
+    Ok(())
+}
+
+pub fn update_obligation_borrow_factor_multiplier(
+    obligation: &mut Obligation,
+    borrow_factor_multiplier_bps: u64,
+    slot: Slot,
+) -> Result<()> {
+    if borrow_factor_multiplier_bps != 0
+        && !(MIN_OBLIGATION_BORROW_FACTOR_MULTIPLIER_BPS
+            ..=MAX_OBLIGATION_BORROW_FACTOR_MULTIPLIER_BPS)
+            .contains(&borrow_factor_multiplier_bps)
+    {
+        msg!(
+            "Borrow factor multiplier must be 0 (unset) or in range [{}, {}] bps",
+            MIN_OBLIGATION_BORROW_FACTOR_MULTIPLIER_BPS,
+            MAX_OBLIGATION_BORROW_FACTOR_MULTIPLIER_BPS
+        );
+        return err!(LendingError::InvalidConfig);
+    }
+
+    let effective_multiplier_bps = |multiplier_bps: u64| {
+        if multiplier_bps == 0 {
+            u64::from(FULL_BPS)
+        } else {
+            multiplier_bps
+        }
+    };
+    let previous_multiplier_bps = effective_multiplier_bps(obligation.borrow_factor_multiplier_bps);
+    let new_multiplier_bps = effective_multiplier_bps(borrow_factor_multiplier_bps);
+
+    if new_multiplier_bps < u64::from(FULL_BPS)
+        && !obligation.is_borrow_factor_discount_whitelisted()
+    {
+        msg!("A borrow factor multiplier below 100% requires the obligation to be whitelisted");
+        return err!(LendingError::InvalidConfig);
+    }
+
+    if new_multiplier_bps > previous_multiplier_bps && !obligation.borrows_empty() {
+        if obligation
+            .last_update
+            .is_stale(slot, PriceStatusFlags::NONE)?
+        {
+            msg!("Obligation must be refreshed in the current slot to increase its borrow factor multiplier");
+            return err!(LendingError::ObligationStale);
+        }
+
+        // Each borrow's factor adjusted value grows at most by the ratio of the multipliers
+        let max_debt_value_f = Fraction::from_bits(obligation.borrow_factor_adjusted_debt_value_sf)
+            * u128::from(new_multiplier_bps)
+            / u128::from(previous_multiplier_bps);
+        let unhealthy_borrow_value_f = Fraction::from_bits(obligation.unhealthy_borrow_value_sf);
+        if max_debt_value_f > unhealthy_borrow_value_f {
+            msg!(
+                "Borrow factor multiplier increase could make the obligation liquidatable: debt value up to {} for an unhealthy borrow value of {}",
+                max_debt_value_f.to_display(),
+                unhealthy_borrow_value_f.to_display()
+            );
+            return err!(LendingError::InvalidConfig);
+        }
+    }
+
+    msg!(
+        "Updating obligation borrow factor multiplier from {} to {} bps",
+        obligation.borrow_factor_multiplier_bps,
+        borrow_factor_multiplier_bps
+    );
+    obligation.borrow_factor_multiplier_bps = borrow_factor_multiplier_bps;
+    obligation.last_update.mark_stale();
+
+    Ok(())
+}
+
+pub fn update_obligation_borrow_factor_discount_whitelist(
+    obligation: &mut Obligation,
+    whitelisted: bool,
+) -> Result<()> {
+    if !whitelisted
+        && obligation.borrow_factor_multiplier_bps != 0
+        && obligation.borrow_factor_multiplier_bps < u64::from(FULL_BPS)
+    {
+        msg!(
+            "Borrow factor multiplier {} bps must be raised to at least 100% before removing the obligation from the whitelist",
+            obligation.borrow_factor_multiplier_bps
+        );
+        return err!(LendingError::InvalidConfig);
+    }
+
+    msg!(
+        "Updating obligation borrow factor discount whitelist from {} to {}",
+        obligation.is_borrow_factor_discount_whitelisted(),
+        whitelisted
+    );
+    obligation.borrow_factor_discount_whitelisted = whitelisted as u8;
+
+    Ok(())
+}
+
 pub fn add_referrer_fee(
     borrow_reserve: &mut Reserve,
     referrer_token_state: &mut ReferrerTokenState,
     referrer_fee: Fraction,
 ) -> Result<()> {
+    let max_referrer_fees = borrow_reserve.config.max_referrer_fees;
+    let referrer_fee = if max_referrer_fees > 0 {
+        let remaining_referrer_fees = Fraction::from(max_referrer_fees).saturating_sub(
+            Fraction::from_bits(borrow_reserve.liquidity.accumulated_referrer_fees_sf),
+        );
+        let capped_referrer_fee = min(referrer_fee, remaining_referrer_fees);
+        let excess_referrer_fee = referrer_fee - capped_referrer_fee;
+        if excess_referrer_fee > Fraction::ZERO {
+            msg!(
+                "Referrer fees cap reached, accruing {} as protocol fees",
+                excess_referrer_fee.to_display()
+            );
+            borrow_reserve.liquidity.accumulated_protocol_fees_sf += excess_referrer_fee.to_sf();
+        }
+        capped_referrer_fee
+    } else {
+        referrer_fee
+    };
+
     let referrer_fee_sf = referrer_fee.to_sf();
     referrer_token_state.amount_cumulative_sf += referrer_fee_sf;
     referrer_token_state.amount_unclaimed_sf += referrer_fee_sf;
@@ -1609,6 +2995,71 @@ pub fn add_referrer_fee(
     Ok(())
 }
 
+pub fn claim_supply_referrer_fees(
+    reserve: &mut Reserve,
+    referrer_token_state: &mut ReferrerTokenState,
+) -> Result<Fraction> {
+    let supply_referrer_fees =
+        Fraction::from_bits(reserve.liquidity.pending_supply_referrer_fees_sf);
+
+    if supply_referrer_fees == Fraction::ZERO {
+        return Ok(Fraction::ZERO);
+    }
+
+    reserve.liquidity.pending_supply_referrer_fees_sf = 0;
+    add_referrer_fee(reserve, referrer_token_state, supply_referrer_fees)?;
+
+    Ok(supply_referrer_fees)
+}
+
+fn calculate_net_new_variable_debt(
+    borrow_reserve: &Reserve,
+    slots_elapsed: u64,
+    borrowed_amount_f: Fraction,
+    previous_borrowed_amount_f: Fraction,
+) -> Result<Fraction> {
+    let fixed_rate = approximate_compounded_interest(
+        Fraction::from_bps(borrow_reserve.config.host_fixed_interest_rate_bps),
+        slots_elapsed,
+    );
+    let net_new_debt = borrowed_amount_f - previous_borrowed_amount_f;
+    let net_new_fixed_debt = previous_borrowed_amount_f * fixed_rate - previous_borrowed_amount_f;
+    if net_new_fixed_debt > net_new_debt {
+        return Err(LendingError::CannotCalculateReferralAmountDueToSlotsMismatch.into());
+    }
+    Ok(net_new_debt - net_new_fixed_debt)
+}
+
+/// Whether refreshing the obligation against the reserve in its current state would fail with
+/// `CannotCalculateReferralAmountDueToSlotsMismatch`, in which case the reserve must be refreshed
+/// first.
+pub fn is_referrer_fees_slots_mismatch(
+    borrow_reserve: &Reserve,
+    obligation: &Obligation,
+    borrow_reserve_pk: Pubkey,
+    slot: Slot,
+) -> Result<bool> {
+    if borrow_reserve.liquidity.absolute_referral_rate_sf == 0 {
+        return Ok(false);
+    }
+
+    let (liquidity, _) = obligation.find_liquidity_in_borrows(borrow_reserve_pk)?;
+    let mut liquidity = *liquidity;
+
+    let previous_borrowed_amount_f = Fraction::from_bits(liquidity.borrowed_amount_sf);
+    liquidity.accrue_interest(BigFraction::from(
+        borrow_reserve.liquidity.cumulative_borrow_rate_bsf,
+    ))?;
+
+    Ok(calculate_net_new_variable_debt(
+        borrow_reserve,
+        obligation.last_update.slots_elapsed(slot)?,
+        Fraction::from_bits(liquidity.borrowed_amount_sf),
+        previous_borrowed_amount_f,
+    )
+    .is_err())
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn accumulate_referrer_fees<'info, T>(
     borrow_reserve_info_key: Pubkey,
@@ -1631,16 +3082,12 @@ where
         return Ok(());
     }
 
-    let fixed_rate = approximate_compounded_interest(
-        Fraction::from_bps(borrow_reserve.config.host_fixed_interest_rate_bps),
+    let net_new_variable_debt_f = calculate_net_new_variable_debt(
+        borrow_reserve,
         slots_elapsed,
-    );
-    let net_new_debt = borrowed_amount_f - previous_borrowed_amount_f;
-    let net_new_fixed_debt = previous_borrowed_amount_f * fixed_rate - previous_borrowed_amount_f;
-    if net_new_fixed_debt > net_new_debt {
-        return Err(LendingError::CannotCalculateReferralAmountDueToSlotsMismatch.into());
-    }
-    let net_new_variable_debt_f = net_new_debt - net_new_fixed_debt;
+        borrowed_amount_f,
+        previous_borrowed_amount_f,
+    )?;
 
     let referrer_fee_f = net_new_variable_debt_f * absolute_referral_rate;
 
@@ -1709,6 +3156,64 @@ pub fn withdraw_referrer_fees(
     Ok(withdraw_amount)
 }
 
+pub fn update_elevation_group(
+    lending_market: &mut LendingMarket,
+    elevation_group: ElevationGroup,
+) -> Result<()> {
+    if elevation_group.id > MAX_NUM_ELEVATION_GROUPS {
+        return err!(LendingError::InvalidElevationGroupConfig);
+    }
+
+    if elevation_group.id != ELEVATION_GROUP_NONE && elevation_group.liquidation_threshold_pct == 0
+    {
+        return err!(LendingError::InvalidElevationGroupConfig);
+    }
+
+    if elevation_group.liquidation_threshold_pct >= 100
+        || elevation_group.ltv_pct >= 100
+        || elevation_group.ltv_pct > elevation_group.liquidation_threshold_pct
+        || elevation_group.max_liquidation_bonus_bps > FULL_BPS
+    {
+        return err!(LendingError::InvalidElevationGroupConfig);
+    }
+
+    if elevation_group.id != ELEVATION_GROUP_NONE
+        && elevation_group.liquidation_threshold_pct - elevation_group.ltv_pct
+            < lending_market.min_elevation_group_ltv_spread_pct
+    {
+        msg!(
+            "Liquidation threshold must be at least {}% above the LTV",
+            lending_market.min_elevation_group_ltv_spread_pct
+        );
+        return err!(LendingError::InvalidElevationGroupConfig);
+    }
+
+    if elevation_group.id != ELEVATION_GROUP_NONE
+        && (elevation_group.debt_reserve == Pubkey::default()
+            || elevation_group.max_reserves_as_collateral == 0)
+    {
+        return err!(LendingError::InvalidElevationGroupConfig);
+    }
+
+    if Fraction::from_percent(elevation_group.liquidation_threshold_pct)
+        + Fraction::from_percent(elevation_group.liquidation_threshold_pct)
+            * Fraction::from_bps(elevation_group.max_liquidation_bonus_bps)
+        > Fraction::ONE
+    {
+        msg!("Max liquidation bonus * liquidation threshold is greater than 100%, invalid");
+        return err!(LendingError::InvalidElevationGroupConfig);
+    }
+
+    let prev_elevation_group = lending_market.get_elevation_group(elevation_group.id);
+
+    msg!("Prev value is {:?}", prev_elevation_group);
+    msg!("New value is {:?}", elevation_group);
+
+    lending_market.set_elevation_group(elevation_group)?;
+
+    Ok(())
+}
+
 pub fn update_reserve_config(reserve: &mut Reserve, mode: UpdateConfigMode, value: &[u8]) {
     match mode {
         UpdateConfigMode::UpdateLoanToValuePct => {
@@ -1760,6 +3265,13 @@ pub fn update_reserve_config(reserve: &mut Reserve, mode: UpdateConfigMode, valu
             msg!("Prv Value is {}", Fraction::from_bits(prv.into()));
             msg!("New Value is {}", Fraction::from_bits(new.into()));
         }
+        UpdateConfigMode::UpdateFeesFlashLoanProtocolFee => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.fees.flash_loan_protocol_fee_sf;
+            reserve.config.fees.flash_loan_protocol_fee_sf = new;
+            msg!("Prv Value is {}", Fraction::from_bits(prv.into()));
+            msg!("New Value is {}", Fraction::from_bits(new.into()));
+        }
         UpdateConfigMode::UpdateFeesReferralFeeBps => {
             msg!("ReferralFee moved to lending_market");
         }
@@ -2068,47 +3580,222 @@ pub fn update_reserve_config(reserve: &mut Reserve, mode: UpdateConfigMode, valu
                     .borrow_limit_against_this_collateral_in_elevation_group
             );
         }
-        UpdateConfigMode::UpdateFarmCollateral => {
-            let new: [u8; 32] = value[0..32].try_into().unwrap();
-            let new = Pubkey::new_from_array(new);
-            let prv = reserve.farm_collateral;
-            reserve.farm_collateral = new;
+        UpdateConfigMode::UpdateFarmCollateral => {
+            let new: [u8; 32] = value[0..32].try_into().unwrap();
+            let new = Pubkey::new_from_array(new);
+            let prv = reserve.farm_collateral;
+            reserve.farm_collateral = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateFarmDebt => {
+            let new: [u8; 32] = value[0..32].try_into().unwrap();
+            let new = Pubkey::new_from_array(new);
+            let prv = reserve.farm_debt;
+            reserve.farm_debt = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateDisableUsageAsCollateralOutsideEmode => {
+            let new = value[0];
+            let prv = reserve.config.disable_usage_as_coll_outside_emode;
+            reserve.config.disable_usage_as_coll_outside_emode = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateDisableUsageAsCollateral => {
+            let new = value[0];
+            let prv = reserve.config.disable_usage_as_collateral;
+            reserve.config.disable_usage_as_collateral = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateBlockBorrowingAboveUtilization => {
+            let new = value[0];
+            let prv = reserve.config.utilization_limit_block_borrowing_above;
+            reserve.config.utilization_limit_block_borrowing_above = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateBlockPriceUsage => {
+            let new = value[0];
+            let prv = reserve.config.token_info.block_price_usage;
+            reserve.config.token_info.block_price_usage = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateHostFixedInterestRateBps => {
+            let new = u16::from_le_bytes(value[..2].try_into().unwrap());
+            let prv = reserve.config.host_fixed_interest_rate_bps;
+            reserve.config.host_fixed_interest_rate_bps = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateLimitsInQuoteValue => {
+            let new = value[0];
+            let prv = reserve.config.limits_in_quote_value;
+            reserve.config.limits_in_quote_value = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateLiquidatorAllowlist => {
+            let new: [Pubkey; 4] = BorshDeserialize::try_from_slice(value).unwrap();
+            let prv = reserve.liquidator_allowlist;
+            reserve.liquidator_allowlist = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateProtocolTakeRateBands => {
+            msg!("Prv Value is {:?}", reserve.config.protocol_take_rate_bands);
+            reserve.config.protocol_take_rate_bands =
+                BorshDeserialize::try_from_slice(value).unwrap();
+            msg!("New Value is {:?}", reserve.config.protocol_take_rate_bands);
+        }
+        UpdateConfigMode::UpdateMinBorrowAmount => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.min_borrow_amount;
+            reserve.config.min_borrow_amount = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateMaxDepositPerObligation => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.max_deposit_per_obligation;
+            reserve.config.max_deposit_per_obligation = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateMinBorrowRateBps => {
+            let new = u32::from_le_bytes(value[..4].try_into().unwrap());
+            let prv = reserve.config.min_borrow_rate_bps;
+            reserve.config.min_borrow_rate_bps = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateMaxBorrowRateBps => {
+            let new = u32::from_le_bytes(value[..4].try_into().unwrap());
+            let prv = reserve.config.max_borrow_rate_bps;
+            reserve.config.max_borrow_rate_bps = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateMaxReferrerFees => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.max_referrer_fees;
+            reserve.config.max_referrer_fees = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateSupplyReferrer => {
+            let new: [u8; 32] = value[0..32].try_into().unwrap();
+            let new = Pubkey::new_from_array(new);
+            let prv = reserve.config.supply_referrer;
+            reserve.config.supply_referrer = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateSupplyReferralFeeBps => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.supply_referral_fee_bps;
+            reserve.config.supply_referral_fee_bps = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateElevationGroupOriginationFeeDiscountBps => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.elevation_group_origination_fee_discount_bps;
+            reserve.config.elevation_group_origination_fee_discount_bps = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateMaxConsecutiveStalePriceRefreshes => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.max_consecutive_stale_price_refreshes;
+            reserve.config.max_consecutive_stale_price_refreshes = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateStalePriceBorrowingDisabled => {
+            let new = value[0];
+            let prv = reserve.liquidity.stale_price_borrowing_disabled;
+            reserve.liquidity.stale_price_borrowing_disabled = new;
+            if new == 0 {
+                reserve.liquidity.consecutive_stale_price_refreshes = 0;
+            }
             msg!("Prv Value is {:?}", prv);
             msg!("New Value is {:?}", new);
         }
-        UpdateConfigMode::UpdateFarmDebt => {
+        UpdateConfigMode::UpdateForgiveProtocolFeeDust => {
+            let new = value[0];
+            let prv = reserve.config.forgive_protocol_fee_dust;
+            reserve.config.forgive_protocol_fee_dust = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateHostFeeReceiver => {
             let new: [u8; 32] = value[0..32].try_into().unwrap();
             let new = Pubkey::new_from_array(new);
-            let prv = reserve.farm_debt;
-            reserve.farm_debt = new;
+            let prv = reserve.config.host_fee_receiver;
+            reserve.config.host_fee_receiver = new;
             msg!("Prv Value is {:?}", prv);
             msg!("New Value is {:?}", new);
         }
-        UpdateConfigMode::UpdateDisableUsageAsCollateralOutsideEmode => {
-            let new = value[0];
-            let prv = reserve.config.disable_usage_as_coll_outside_emode;
-            reserve.config.disable_usage_as_coll_outside_emode = new;
+        UpdateConfigMode::UpdateFeesRedeemFee => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.redeem_fee_sf;
+            reserve.config.redeem_fee_sf = new;
+            msg!("Prv Value is {}", Fraction::from_bits(prv.into()));
+            msg!("New Value is {}", Fraction::from_bits(new.into()));
+        }
+        UpdateConfigMode::UpdateMaxInterestAccrualSlots => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.max_interest_accrual_slots;
+            reserve.config.max_interest_accrual_slots = new;
             msg!("Prv Value is {:?}", prv);
             msg!("New Value is {:?}", new);
         }
-        UpdateConfigMode::UpdateBlockBorrowingAboveUtilization => {
-            let new = value[0];
-            let prv = reserve.config.utilization_limit_block_borrowing_above;
-            reserve.config.utilization_limit_block_borrowing_above = new;
+        UpdateConfigMode::UpdateMaxOracleRotationPriceDivergenceBps => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.max_oracle_rotation_price_divergence_bps;
+            reserve.config.max_oracle_rotation_price_divergence_bps = new;
             msg!("Prv Value is {:?}", prv);
             msg!("New Value is {:?}", new);
         }
-        UpdateConfigMode::UpdateBlockPriceUsage => {
+        UpdateConfigMode::UpdateTokenInfoQuoteCurrency => {
+            let new: [u8; 32] = value[0..32].try_into().unwrap();
+            let prv = reserve.config.token_info.quote_currency;
+            reserve.config.token_info.quote_currency = new;
+            msg!(
+                "Prv Value is {}",
+                std::str::from_utf8(&prv)
+                    .unwrap_or("InvalidQuoteCurrency")
+                    .trim_end_matches('\0')
+            );
+            msg!(
+                "New Value is {}",
+                std::str::from_utf8(&new)
+                    .unwrap_or("InvalidQuoteCurrency")
+                    .trim_end_matches('\0')
+            );
+        }
+        UpdateConfigMode::UpdateBorrowCollateralHaircutBps => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.borrow_collateral_haircut_bps;
+            reserve.config.borrow_collateral_haircut_bps = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoPriceFeedPriority => {
             let new = value[0];
-            let prv = reserve.config.token_info.block_price_usage;
-            reserve.config.token_info.block_price_usage = new;
+            let prv = reserve.config.token_info.price_feed_priority;
+            reserve.config.token_info.price_feed_priority = new;
             msg!("Prv Value is {:?}", prv);
             msg!("New Value is {:?}", new);
         }
-        UpdateConfigMode::UpdateHostFixedInterestRateBps => {
-            let new = u16::from_le_bytes(value[..2].try_into().unwrap());
-            let prv = reserve.config.host_fixed_interest_rate_bps;
-            reserve.config.host_fixed_interest_rate_bps = new;
+        UpdateConfigMode::UpdateTokenInfoPriceMaxAgeGrace => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.token_info.max_age_price_grace_seconds;
+            reserve.config.token_info.max_age_price_grace_seconds = new;
             msg!("Prv Value is {:?}", prv);
             msg!("New Value is {:?}", new);
         }
@@ -2123,6 +3810,31 @@ pub fn update_reserve_config(reserve: &mut Reserve, mode: UpdateConfigMode, valu
     reserve.last_update.mark_stale();
 }
 
+pub fn check_oracle_rotation_price_divergence(
+    previous_price: Fraction,
+    new_price: Fraction,
+    max_price_divergence_bps: u64,
+) -> Result<()> {
+    let divergence = if new_price > previous_price {
+        new_price - previous_price
+    } else {
+        previous_price - new_price
+    };
+    let max_divergence = previous_price * Fraction::from_bps(max_price_divergence_bps);
+
+    if divergence > max_divergence {
+        msg!(
+            "New oracle price {} diverges from the stored price {} by more than {} bps",
+            new_price.to_display(),
+            previous_price.to_display(),
+            max_price_divergence_bps
+        );
+        return err!(LendingError::OracleRotationPriceDivergence);
+    }
+
+    Ok(())
+}
+
 pub mod utils {
     use anchor_lang::require_neq;
 
@@ -2130,7 +3842,10 @@ pub mod utils {
     use crate::{
         fraction::FRACTION_ONE_SCALED,
         state::ReserveConfig,
-        utils::{ten_pow, ELEVATION_GROUP_NONE, FULL_BPS, MAX_NUM_ELEVATION_GROUPS},
+        utils::{
+            borrow_rate_curve::MAX_UTILIZATION_RATE_BPS, ten_pow, ELEVATION_GROUP_NONE, FULL_BPS,
+            MAX_NUM_ELEVATION_GROUPS,
+        },
         ElevationGroup, ObligationCollateral, ObligationLiquidity,
     };
 
@@ -2298,6 +4013,23 @@ pub mod utils {
         Ok(())
     }
 
+    pub fn check_obligation_obsolete_reserves_limit(
+        market: &LendingMarket,
+        obligation: &Obligation,
+    ) -> Result<()> {
+        if market.max_obsolete_reserves_per_obligation > 0
+            && obligation.num_of_obsolete_reserves > market.max_obsolete_reserves_per_obligation
+        {
+            msg!(
+                "Obligation holds {} obsolete reserves, max allowed {}",
+                obligation.num_of_obsolete_reserves,
+                market.max_obsolete_reserves_per_obligation
+            );
+            return err!(LendingError::ObligationTooManyObsoleteReserves);
+        }
+        Ok(())
+    }
+
     pub fn check_elevation_group_borrow_limit_constraints<'info, T>(
         obligation: &Obligation,
         elevation_group: Option<&ElevationGroup>,
@@ -2728,6 +4460,7 @@ pub mod utils {
         reserve: &Reserve,
         liquidity_asset_mv: Fraction,
         min_accepted_net_value: Fraction,
+        min_borrow_health_buffer_bps: u64,
     ) -> Result<()> {
         let asset_mv = calculate_market_value_from_liquidity_amount(reserve, amount)?;
 
@@ -2739,6 +4472,17 @@ pub mod utils {
             Fraction::from_bits(obligation.borrowed_assets_market_value_sf) + asset_mv;
         let new_liquidity_asset_mv = liquidity_asset_mv + asset_mv;
 
+        if let Some(self_borrow_limit_value) = obligation.self_borrow_limit_value_f() {
+            if new_total_bf_debt_mv > self_borrow_limit_value {
+                msg!(
+                    "Obligation new borrow factor adjusted debt value {} exceeds the owner set limit {}",
+                    new_total_bf_debt_mv.to_display(),
+                    self_borrow_limit_value.to_display()
+                );
+                return err!(LendingError::ObligationSelfBorrowLimitExceeded);
+            }
+        }
+
         if new_liquidity_asset_mv > 0 && new_liquidity_asset_mv < min_accepted_net_value {
             msg!(
                 "Obligation new borrowed value after borrow {} for {}",
@@ -2759,6 +4503,21 @@ pub mod utils {
             return err!(LendingError::WorseLTVBlocked);
         }
 
+        if min_borrow_health_buffer_bps > 0 {
+            let max_ltv = obligation
+                .unhealthy_loan_to_value()
+                .saturating_sub(Fraction::from_bps(min_borrow_health_buffer_bps));
+            if new_ltv > max_ltv {
+                msg!(
+                    "Obligation new LTV after borrow {:.2} is within the {} bps health buffer of the unhealthy LTV {:.2}",
+                    new_ltv.to_display(),
+                    min_borrow_health_buffer_bps,
+                    obligation.unhealthy_loan_to_value().to_display()
+                );
+                return err!(LendingError::BorrowTooLarge);
+            }
+        }
+
         if new_total_no_bf_debt_mv >= Fraction::from_bits(obligation.deposited_value_sf) {
             msg!(
                 "Obligation can't have more liabilities than assets after borrow {} of {}",
@@ -2844,17 +4603,23 @@ pub mod utils {
         deposit_reserve: &Reserve,
         elevation_group: Option<&ElevationGroup>,
     ) -> Result<(u8, u8)> {
-        if let Some(elevation_group) = elevation_group {
-            Ok((
+        let (ltv_pct, liquidation_threshold_pct) = if let Some(elevation_group) = elevation_group {
+            (
                 elevation_group.ltv_pct,
                 elevation_group.liquidation_threshold_pct,
-            ))
+            )
         } else {
-            Ok((
+            (
                 deposit_reserve.config.loan_to_value_pct,
                 deposit_reserve.config.liquidation_threshold_pct,
-            ))
+            )
+        };
+
+        if deposit_reserve.config.disable_usage_as_collateral > 0 {
+            return Ok((0, liquidation_threshold_pct));
         }
+
+        Ok((ltv_pct, liquidation_threshold_pct))
     }
 
     pub fn check_obligation_fully_refreshed_and_not_null(
@@ -2963,6 +4728,31 @@ pub mod utils {
             msg!("Borrow fee must be in range [0, 100%]");
             return err!(LendingError::InvalidConfig);
         }
+        if config.max_borrow_rate_bps > 0 && config.min_borrow_rate_bps > config.max_borrow_rate_bps
+        {
+            msg!("Invalid 'min borrow rate', must not be above 'max borrow rate' when enabled");
+            return err!(LendingError::InvalidConfig);
+        }
+        if config.elevation_group_origination_fee_discount_bps > u64::from(FULL_BPS) {
+            msg!("Elevation group origination fee discount must be in range [0, 10000] bps");
+            return err!(LendingError::InvalidConfig);
+        }
+        if config.supply_referral_fee_bps > u64::from(FULL_BPS) {
+            msg!("Supply referral fee must be in range [0, 10000] bps");
+            return err!(LendingError::InvalidConfig);
+        }
+        if config.borrow_collateral_haircut_bps > u64::from(FULL_BPS) {
+            msg!("Borrow collateral haircut must be in range [0, 10000] bps");
+            return err!(LendingError::InvalidConfig);
+        }
+        if u128::from(config.fees.flash_loan_protocol_fee_sf) >= FRACTION_ONE_SCALED {
+            msg!("Flash loan protocol fee must be in range [0, 100%]");
+            return err!(LendingError::InvalidConfig);
+        }
+        if u128::from(config.redeem_fee_sf) >= FRACTION_ONE_SCALED {
+            msg!("Redeem fee must be in range [0, 100%]");
+            return err!(LendingError::InvalidConfig);
+        }
         if config.protocol_liquidation_fee_pct > 100 {
             msg!("Protocol liquidation fee must be in range [0, 100]");
             return err!(LendingError::InvalidConfig);
@@ -2971,6 +4761,46 @@ pub mod utils {
             msg!("Protocol take rate must be in range [0, 100]");
             return err!(LendingError::InvalidConfig);
         }
+        let (min_protocol_take_rate_pct, max_protocol_take_rate_pct) =
+            market.protocol_take_rate_pct_bounds();
+        if config.protocol_take_rate_pct < min_protocol_take_rate_pct
+            || config.protocol_take_rate_pct > max_protocol_take_rate_pct
+        {
+            msg!(
+                "Protocol take rate must be in the market range [{}, {}]",
+                min_protocol_take_rate_pct,
+                max_protocol_take_rate_pct
+            );
+            return err!(LendingError::InvalidConfig);
+        }
+        let mut last_band_utilization_rate_bps = None;
+        for band in config
+            .protocol_take_rate_bands
+            .iter()
+            .filter(|band| band.is_set())
+        {
+            if band.protocol_take_rate_pct < min_protocol_take_rate_pct
+                || band.protocol_take_rate_pct > max_protocol_take_rate_pct
+            {
+                msg!(
+                    "Protocol take rate band must be in the market range [{}, {}]",
+                    min_protocol_take_rate_pct,
+                    max_protocol_take_rate_pct
+                );
+                return err!(LendingError::InvalidConfig);
+            }
+            if band.utilization_rate_bps > MAX_UTILIZATION_RATE_BPS {
+                msg!("Protocol take rate band utilization must be in range [0, 100%]");
+                return err!(LendingError::InvalidConfig);
+            }
+            if last_band_utilization_rate_bps
+                .is_some_and(|last_bps| band.utilization_rate_bps <= last_bps)
+            {
+                msg!("Protocol take rate bands must be sorted by utilization rate");
+                return err!(LendingError::InvalidConfig);
+            }
+            last_band_utilization_rate_bps = Some(band.utilization_rate_bps);
+        }
         if !config.token_info.is_valid() {
             msg!("Invalid reserve token info");
             return err!(LendingError::InvalidOracleConfig);
@@ -2979,6 +4809,10 @@ pub mod utils {
             msg!("Invalid reserve token twap config");
             return err!(LendingError::InvalidTwapConfig);
         }
+        if !config.token_info.is_price_feed_priority_valid() {
+            msg!("Invalid reserve price feed priority");
+            return err!(LendingError::InvalidOracleConfig);
+        }
 
         if config.bad_debt_liquidation_bonus_bps >= 100 {
             msg!("Invalid bad debt liquidation bonus, cannot be more than 1%");
@@ -3012,6 +4846,40 @@ pub mod utils {
             msg!("Invalid 'borrow limit', must be at least equal to 'borrow limit outside elevation group' when enabled");
             return err!(LendingError::InvalidConfig);
         }
+        if config.limits_in_quote_value > 1 {
+            msg!("Invalid 'limits in quote value' flag, must be 0 or 1");
+            return err!(LendingError::InvalidFlag);
+        }
+        if config.disable_usage_as_collateral > 1 {
+            msg!("Invalid 'disable usage as collateral' flag, must be 0 or 1");
+            return err!(LendingError::InvalidFlag);
+        }
+
+        if config.forgive_protocol_fee_dust > 1 {
+            msg!("Invalid 'forgive protocol fee dust' flag, must be 0 or 1");
+            return err!(LendingError::InvalidFlag);
+        }
+
+        if config.token_info.quote_currency != [0; 32]
+            && config.token_info.quote_currency != market.quote_currency
+        {
+            msg!("Reserve oracles must quote in the lending market quote currency");
+            return err!(LendingError::InvalidOracleConfig);
+        }
+
+        let num_elevation_groups = config
+            .elevation_groups
+            .iter()
+            .filter(|id| **id != ELEVATION_GROUP_NONE)
+            .count();
+        if num_elevation_groups > market.max_elevation_groups_per_reserve().into() {
+            msg!(
+                "Reserve is in {} elevation groups, max allowed by the market is {}",
+                num_elevation_groups,
+                market.max_elevation_groups_per_reserve()
+            );
+            return err!(LendingError::InvalidConfig);
+        }
 
         for elevation_group_id in config.elevation_groups {
             if let Some(elevation_group) = get_elevation_group(elevation_group_id, market)? {
@@ -3112,3 +4980,519 @@ pub mod utils {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Ref, RefCell};
+
+    use super::*;
+    use crate::ObligationLiquidity;
+
+    const SLOT: Slot = 100;
+
+    struct TestAccountLoader<'a, T> {
+        pubkey: Pubkey,
+        account: &'a RefCell<T>,
+    }
+
+    impl<'info, T> AnyAccountLoader<'info, T> for TestAccountLoader<'_, T> {
+        fn get_mut(&self) -> Result<RefMut<T>> {
+            Ok(self.account.borrow_mut())
+        }
+
+        fn get(&self) -> Result<Ref<T>> {
+            Ok(self.account.borrow())
+        }
+
+        fn get_pubkey(&self) -> Pubkey {
+            self.pubkey
+        }
+    }
+
+    fn fresh_reserve() -> Reserve {
+        let mut reserve = Reserve::default();
+        reserve.version = PROGRAM_VERSION as u64;
+        reserve.liquidity.market_price_sf = Fraction::ONE.to_bits();
+        reserve.config.loan_to_value_pct = 50;
+        reserve.config.liquidation_threshold_pct = 80;
+        reserve
+            .last_update
+            .update_slot(SLOT, PriceStatusFlags::ALL_CHECKS);
+        reserve
+    }
+
+    fn deposit(reserve: Pubkey, amount: u64) -> ObligationCollateral {
+        ObligationCollateral {
+            deposit_reserve: reserve,
+            deposited_amount: amount,
+            market_value_sf: Fraction::from(amount).to_bits(),
+            ..Default::default()
+        }
+    }
+
+    fn fresh_obligation_with_debt(
+        deposits: &[ObligationCollateral],
+        allowed_borrow_value: u64,
+        debt_value: u64,
+        unhealthy_borrow_value: u64,
+    ) -> Obligation {
+        let mut obligation = Obligation::default();
+        for (i, collateral) in deposits.iter().enumerate() {
+            obligation.deposits[i] = *collateral;
+        }
+        obligation.deposited_value_sf = Fraction::from(
+            deposits
+                .iter()
+                .map(|collateral| collateral.deposited_amount)
+                .sum::<u64>(),
+        )
+        .to_bits();
+        obligation.borrows[0] = ObligationLiquidity {
+            borrow_reserve: Pubkey::new_unique(),
+            borrowed_amount_sf: Fraction::from(debt_value).to_bits(),
+            market_value_sf: Fraction::from(debt_value).to_bits(),
+            ..Default::default()
+        };
+        obligation.allowed_borrow_value_sf = Fraction::from(allowed_borrow_value).to_bits();
+        obligation.borrow_factor_adjusted_debt_value_sf = Fraction::from(debt_value).to_bits();
+        obligation.borrowed_assets_market_value_sf = Fraction::from(debt_value).to_bits();
+        obligation.unhealthy_borrow_value_sf = Fraction::from(unhealthy_borrow_value).to_bits();
+        obligation
+            .last_update
+            .update_slot(SLOT, PriceStatusFlags::ALL_CHECKS);
+        obligation
+    }
+
+    #[test]
+    fn max_withdraw_of_tiny_position_with_debt_empties_the_position() {
+        let market = LendingMarket::default();
+        let mut withdraw_reserve = fresh_reserve();
+        let withdraw_reserve_pk = Pubkey::new_unique();
+        let mut obligation = fresh_obligation_with_debt(
+            &[
+                deposit(withdraw_reserve_pk, 3),
+                deposit(Pubkey::new_unique(), 1_000),
+            ],
+            500,
+            100,
+            800,
+        );
+
+        let withdrawn = withdraw_obligation_collateral(
+            &market,
+            &mut withdraw_reserve,
+            &mut obligation,
+            u64::MAX,
+            SLOT,
+            withdraw_reserve_pk,
+        )
+        .unwrap();
+
+        assert_eq!(withdrawn, 3);
+        assert_eq!(obligation.deposits[0].deposited_amount, 0);
+        assert_eq!(obligation.deposits[0].deposit_reserve, Pubkey::default());
+        assert!(obligation
+            .position_of_collateral_in_deposits(withdraw_reserve_pk)
+            .is_err());
+    }
+
+    #[test]
+    fn max_withdraw_of_tiny_position_without_debt_empties_the_position() {
+        let market = LendingMarket::default();
+        let mut withdraw_reserve = fresh_reserve();
+        let withdraw_reserve_pk = Pubkey::new_unique();
+        let mut obligation = Obligation::default();
+        obligation.deposits[0] = deposit(withdraw_reserve_pk, 1);
+        obligation.last_update.update_slot(SLOT, None);
+
+        let withdrawn = withdraw_obligation_collateral(
+            &market,
+            &mut withdraw_reserve,
+            &mut obligation,
+            u64::MAX,
+            SLOT,
+            withdraw_reserve_pk,
+        )
+        .unwrap();
+
+        assert_eq!(withdrawn, 1);
+        assert_eq!(obligation.deposits[0].deposit_reserve, Pubkey::default());
+    }
+
+    #[test]
+    fn max_withdraw_limited_by_ltv_keeps_the_remaining_collateral() {
+        let market = LendingMarket::default();
+        let mut withdraw_reserve = fresh_reserve();
+        let withdraw_reserve_pk = Pubkey::new_unique();
+        let mut obligation =
+            fresh_obligation_with_debt(&[deposit(withdraw_reserve_pk, 4)], 2, 1, 3);
+
+        let withdrawn = withdraw_obligation_collateral(
+            &market,
+            &mut withdraw_reserve,
+            &mut obligation,
+            u64::MAX,
+            SLOT,
+            withdraw_reserve_pk,
+        )
+        .unwrap();
+
+        assert_eq!(withdrawn, 2);
+        assert_eq!(obligation.deposits[0].deposit_reserve, withdraw_reserve_pk);
+        assert_eq!(obligation.deposits[0].deposited_amount, 2);
+    }
+
+    #[test]
+    fn deposit_and_borrow_enters_3x_leverage_without_separate_refresh() {
+        let market = LendingMarket::default();
+        let clock = Clock {
+            slot: SLOT,
+            ..Default::default()
+        };
+
+        let mut collateral_reserve = fresh_reserve();
+        collateral_reserve.config.loan_to_value_pct = 75;
+        collateral_reserve.config.deposit_limit = u64::MAX;
+        collateral_reserve.config.token_info.max_age_price_seconds = 60;
+        let collateral_reserve = RefCell::new(collateral_reserve);
+        let collateral_reserve_pk = Pubkey::new_unique();
+
+        let mut debt_reserve = fresh_reserve();
+        debt_reserve.config.borrow_limit = u64::MAX;
+        debt_reserve.config.borrow_limit_outside_elevation_group = u64::MAX;
+        debt_reserve.liquidity.available_amount = 1_000;
+        let debt_reserve_pk = Pubkey::new_unique();
+
+        let mut obligation = Obligation::default();
+
+        // 100 of the owner's own liquidity and 200 flash borrowed, repaid with the borrow below
+        let collateral_amount = deposit_reserve_liquidity_and_obligation_collateral(
+            &market,
+            &mut collateral_reserve.borrow_mut(),
+            &mut obligation,
+            &clock,
+            300,
+            collateral_reserve_pk,
+        )
+        .unwrap();
+        assert_eq!(collateral_amount, 300);
+
+        refresh_reserve(
+            &mut collateral_reserve.borrow_mut(),
+            &clock,
+            None,
+            market.referral_fee_bps,
+        )
+        .unwrap();
+
+        let deposit_reserves_iter = std::iter::once(TestAccountLoader {
+            pubkey: collateral_reserve_pk,
+            account: &collateral_reserve,
+        });
+        refresh_obligation(
+            &mut obligation,
+            &market,
+            clock.slot,
+            deposit_reserves_iter,
+            std::iter::empty(),
+            std::iter::empty::<TestAccountLoader<ReferrerTokenState>>(),
+        )
+        .unwrap();
+
+        let CalculateBorrowResult { receive_amount, .. } = borrow_obligation_liquidity(
+            &market,
+            &mut debt_reserve,
+            &mut obligation,
+            200,
+            &clock,
+            debt_reserve_pk,
+            None,
+            std::iter::once(TestAccountLoader {
+                pubkey: collateral_reserve_pk,
+                account: &collateral_reserve,
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(receive_amount, 200);
+        assert_eq!(obligation.deposits[0].deposited_amount, 300);
+        assert_eq!(obligation.borrows[0].borrow_reserve, debt_reserve_pk);
+
+        let deposited_value = Fraction::from_bits(obligation.deposited_value_sf);
+        let debt_value = Fraction::from_bits(obligation.borrows[0].borrowed_amount_sf);
+        assert_eq!(
+            deposited_value / (deposited_value - debt_value),
+            Fraction::from(3u64)
+        );
+    }
+
+    #[test]
+    fn stale_price_refreshes_are_counted_once_per_slot() {
+        let mut reserve = fresh_reserve();
+        reserve.config.max_consecutive_stale_price_refreshes = 2;
+
+        for slot in [SLOT + 1, SLOT + 1, SLOT + 2, SLOT + 2] {
+            let clock = Clock {
+                slot,
+                ..Default::default()
+            };
+            refresh_reserve(&mut reserve, &clock, None, 0).unwrap();
+            assert_eq!(
+                reserve.liquidity.consecutive_stale_price_refreshes,
+                slot - SLOT
+            );
+        }
+        assert_eq!(reserve.liquidity.stale_price_borrowing_disabled, 1);
+    }
+
+    fn reserve_with_protocol_fees(available_amount: u64, protocol_fees: Fraction) -> Reserve {
+        let mut reserve = fresh_reserve();
+        reserve.liquidity.available_amount = available_amount;
+        reserve.liquidity.accumulated_protocol_fees_sf = protocol_fees.to_bits();
+        reserve
+    }
+
+    #[test]
+    fn protocol_fee_dust_is_kept_without_forgiveness() {
+        let dust = Fraction::ONE / 2;
+        let mut reserve = reserve_with_protocol_fees(10, dust);
+
+        assert_eq!(
+            redeem_fees(&mut reserve, SLOT).unwrap_err(),
+            LendingError::InsufficientProtocolFeesToRedeem.into()
+        );
+        assert_eq!(
+            Fraction::from_bits(reserve.liquidity.accumulated_protocol_fees_sf),
+            dust
+        );
+    }
+
+    #[test]
+    fn protocol_fee_dust_is_forgiven_when_redeeming() {
+        let mut reserve = reserve_with_protocol_fees(10, Fraction::from_num(5) / 2);
+        reserve.config.forgive_protocol_fee_dust = 1;
+
+        assert_eq!(redeem_fees(&mut reserve, SLOT).unwrap(), 2);
+        assert_eq!(reserve.liquidity.available_amount, 8);
+        assert_eq!(reserve.liquidity.accumulated_protocol_fees_sf, 0);
+
+        let mut reserve = reserve_with_protocol_fees(10, Fraction::ONE / 2);
+        reserve.config.forgive_protocol_fee_dust = 1;
+
+        assert_eq!(redeem_fees(&mut reserve, SLOT).unwrap(), 0);
+        assert_eq!(reserve.liquidity.available_amount, 10);
+        assert_eq!(reserve.liquidity.accumulated_protocol_fees_sf, 0);
+    }
+
+    #[test]
+    fn protocol_fees_above_available_liquidity_are_not_forgiven() {
+        let mut reserve = reserve_with_protocol_fees(1, Fraction::from_num(5) / 2);
+        reserve.config.forgive_protocol_fee_dust = 1;
+
+        assert_eq!(redeem_fees(&mut reserve, SLOT).unwrap(), 1);
+        assert_eq!(reserve.liquidity.available_amount, 0);
+        assert_eq!(
+            Fraction::from_bits(reserve.liquidity.accumulated_protocol_fees_sf),
+            Fraction::from_num(3) / 2
+        );
+    }
+
+    fn reserve_with_debt(max_interest_accrual_slots: u64) -> Reserve {
+        let mut reserve = fresh_reserve();
+        reserve.config.borrow_rate_curve = BorrowRateCurve::new_flat(1_000);
+        reserve.config.max_interest_accrual_slots = max_interest_accrual_slots;
+        reserve.liquidity.available_amount = 1_000_000;
+        reserve.liquidity.borrowed_amount_sf = Fraction::from(1_000_000).to_bits();
+        reserve
+    }
+
+    fn refresh_at(reserve: &mut Reserve, slot: Slot) {
+        let clock = Clock {
+            slot,
+            ..Default::default()
+        };
+        refresh_reserve(reserve, &clock, None, 0).unwrap();
+    }
+
+    #[test]
+    fn clamped_interest_accrual_catches_up_over_refreshes() {
+        let slots_elapsed = 2 * SLOTS_PER_YEAR + 500;
+        let slot = SLOT + slots_elapsed;
+        let mut clamped = reserve_with_debt(SLOTS_PER_YEAR);
+        let mut stepped = reserve_with_debt(0);
+
+        refresh_at(&mut clamped, slot);
+        assert_eq!(clamped.last_update.slot, SLOT + SLOTS_PER_YEAR);
+        assert!(clamped
+            .last_update
+            .is_stale(slot, PriceStatusFlags::NONE)
+            .unwrap());
+
+        refresh_at(&mut clamped, slot);
+        assert_eq!(clamped.last_update.slot, SLOT + 2 * SLOTS_PER_YEAR);
+
+        refresh_at(&mut clamped, slot);
+        assert_eq!(clamped.last_update.slot, slot);
+        assert!(!clamped
+            .last_update
+            .is_stale(slot, PriceStatusFlags::NONE)
+            .unwrap());
+
+        for step_slot in [SLOT + SLOTS_PER_YEAR, SLOT + 2 * SLOTS_PER_YEAR, slot] {
+            refresh_at(&mut stepped, step_slot);
+        }
+        assert_eq!(
+            clamped.liquidity.cumulative_borrow_rate_bsf,
+            stepped.liquidity.cumulative_borrow_rate_bsf
+        );
+        assert_eq!(
+            clamped.liquidity.borrowed_amount_sf,
+            stepped.liquidity.borrowed_amount_sf
+        );
+    }
+
+    #[test]
+    fn unclamped_interest_accrual_over_years_of_slots() {
+        let mut reserve = reserve_with_debt(0);
+
+        refresh_at(&mut reserve, SLOT + 10 * SLOTS_PER_YEAR);
+
+        assert_eq!(reserve.last_update.slot, SLOT + 10 * SLOTS_PER_YEAR);
+        // 10% a year over 10 years, the approximation stays between simple and continuous
+        // compounding
+        let debt = Fraction::from_bits(reserve.liquidity.borrowed_amount_sf);
+        assert!(debt > Fraction::from(2_000_000));
+        assert!(debt < Fraction::from(2_718_282));
+    }
+
+    #[test]
+    fn bad_debt_is_socialized_over_capped_calls() {
+        let reserve_pk = Pubkey::new_unique();
+        let mut reserve = fresh_reserve();
+        reserve.liquidity.available_amount = 1_000;
+        reserve.liquidity.borrowed_amount_sf = Fraction::from(600).to_bits();
+        reserve.collateral.mint_total_supply = 1_600;
+
+        let mut obligation = Obligation::default();
+        obligation.borrows[0] = ObligationLiquidity {
+            borrow_reserve: reserve_pk,
+            borrowed_amount_sf: Fraction::from(600).to_bits(),
+            ..Default::default()
+        };
+
+        let mut liquidity_per_collateral = reserve
+            .collateral_exchange_rate()
+            .unwrap()
+            .fraction_collateral_to_liquidity(Fraction::ONE);
+        for remaining_debt in [400, 200, 0] {
+            reserve
+                .last_update
+                .update_slot(SLOT, PriceStatusFlags::ALL_CHECKS);
+            obligation
+                .last_update
+                .update_slot(SLOT, PriceStatusFlags::ALL_CHECKS);
+
+            let forgiven = socialize_loss(
+                &mut reserve,
+                &reserve_pk,
+                &mut obligation,
+                200,
+                SLOT,
+                std::iter::empty::<TestAccountLoader<Reserve>>(),
+            )
+            .unwrap();
+            assert_eq!(forgiven, Fraction::from(200));
+
+            let new_liquidity_per_collateral = reserve
+                .collateral_exchange_rate()
+                .unwrap()
+                .fraction_collateral_to_liquidity(Fraction::ONE);
+            assert!(new_liquidity_per_collateral < liquidity_per_collateral);
+            liquidity_per_collateral = new_liquidity_per_collateral;
+
+            assert_eq!(
+                reserve.liquidity.borrowed_amount_sf,
+                Fraction::from(remaining_debt).to_bits()
+            );
+            assert_eq!(reserve.version, PROGRAM_VERSION as u64);
+        }
+
+        assert!(obligation.borrows_empty());
+        assert_eq!(
+            reserve.liquidity.total_supply().unwrap(),
+            Fraction::from(1_000)
+        );
+
+        reserve
+            .last_update
+            .update_slot(SLOT, PriceStatusFlags::ALL_CHECKS);
+        obligation
+            .last_update
+            .update_slot(SLOT, PriceStatusFlags::ALL_CHECKS);
+        assert_eq!(
+            socialize_loss(
+                &mut reserve,
+                &reserve_pk,
+                &mut obligation,
+                200,
+                SLOT,
+                std::iter::empty::<TestAccountLoader<Reserve>>(),
+            )
+            .unwrap_err(),
+            LendingError::ObligationEmpty.into()
+        );
+    }
+
+    #[test]
+    fn borrow_factor_discount_requires_whitelist() {
+        let mut obligation =
+            fresh_obligation_with_debt(&[deposit(Pubkey::new_unique(), 100)], 50, 40, 80);
+
+        assert_eq!(
+            update_obligation_borrow_factor_multiplier(&mut obligation, 9_500, SLOT).unwrap_err(),
+            LendingError::InvalidConfig.into()
+        );
+
+        update_obligation_borrow_factor_discount_whitelist(&mut obligation, true).unwrap();
+        update_obligation_borrow_factor_multiplier(&mut obligation, 9_500, SLOT).unwrap();
+        assert_eq!(obligation.borrow_factor_multiplier_bps, 9_500);
+
+        assert_eq!(
+            update_obligation_borrow_factor_discount_whitelist(&mut obligation, false).unwrap_err(),
+            LendingError::InvalidConfig.into()
+        );
+    }
+
+    #[test]
+    fn borrow_factor_multiplier_increase_keeps_obligation_healthy() {
+        let mut obligation =
+            fresh_obligation_with_debt(&[deposit(Pubkey::new_unique(), 100)], 50, 40, 80);
+
+        // 40 * 2 = 80 still within the unhealthy borrow value
+        update_obligation_borrow_factor_multiplier(&mut obligation, 20_000, SLOT).unwrap();
+        assert_eq!(obligation.borrow_factor_multiplier_bps, 20_000);
+
+        let mut obligation =
+            fresh_obligation_with_debt(&[deposit(Pubkey::new_unique(), 100)], 50, 40, 80);
+        assert_eq!(
+            update_obligation_borrow_factor_multiplier(&mut obligation, 20_500, SLOT).unwrap_err(),
+            LendingError::InvalidConfig.into()
+        );
+
+        assert_eq!(
+            update_obligation_borrow_factor_multiplier(&mut obligation, 15_000, SLOT + 1)
+                .unwrap_err(),
+            LendingError::ObligationStale.into()
+        );
+    }
+
+    #[test]
+    fn borrow_factor_multiplier_decrease_doesnt_need_a_fresh_obligation() {
+        let mut obligation =
+            fresh_obligation_with_debt(&[deposit(Pubkey::new_unique(), 100)], 50, 60, 80);
+        obligation.borrow_factor_multiplier_bps = 20_000;
+
+        update_obligation_borrow_factor_multiplier(&mut obligation, 0, SLOT + 1).unwrap();
+        assert_eq!(obligation.borrow_factor_multiplier_bps, 0);
+    }
+}
@@ -50,6 +50,13 @@ pub mod kamino_lending {
         handler_update_lending_market::process(ctx, mode, value)
     }
 
+    pub fn update_elevation_groups(
+        ctx: Context<UpdateElevationGroups>,
+        elevation_groups: Vec<ElevationGroup>,
+    ) -> Result<()> {
+        handler_update_elevation_groups::process(ctx, elevation_groups)
+    }
+
     pub fn update_lending_market_owner(ctx: Context<UpdateLendingMarketOwner>) -> Result<()> {
         handler_update_lending_market_owner::process(ctx)
     }
@@ -71,18 +78,81 @@ pub mod kamino_lending {
         handler_update_reserve_config::process(ctx, mode, &value, skip_validation)
     }
 
+    pub fn validate_reserve_config(
+        ctx: Context<ValidateReserveConfig>,
+        config: Vec<u8>,
+    ) -> Result<()> {
+        handler_validate_reserve_config::process(ctx, &config)
+    }
+
+    pub fn rotate_reserve_oracle(
+        ctx: Context<RotateReserveOracle>,
+        mode: u64,
+        value: Vec<u8>,
+    ) -> Result<()> {
+        handler_rotate_reserve_oracle::process(ctx, mode, &value)
+    }
+
+    pub fn refresh_and_mark_reserve_obsolete(
+        ctx: Context<RefreshAndMarkReserveObsolete>,
+        force: bool,
+    ) -> Result<()> {
+        handler_refresh_and_mark_reserve_obsolete::process(ctx, force)
+    }
+
+    pub fn update_reserve_deprecation(
+        ctx: Context<UpdateReserveDeprecation>,
+        deprecate: bool,
+    ) -> Result<()> {
+        handler_update_reserve_deprecation::process(ctx, deprecate)
+    }
+
     pub fn redeem_fees(ctx: Context<RedeemFees>) -> Result<()> {
         handler_redeem_fees::process(ctx)
     }
 
+    pub fn withdraw_host_fees(ctx: Context<WithdrawHostFees>) -> Result<()> {
+        handler_withdraw_host_fees::process(ctx)
+    }
+
+    pub fn rebalance_reserve_liquidity(
+        ctx: Context<RebalanceReserveLiquidity>,
+        amount: u64,
+    ) -> Result<()> {
+        handler_rebalance_reserve_liquidity::process(ctx, amount)
+    }
+
     pub fn socialize_loss(ctx: Context<SocializeLoss>, liquidity_amount: u64) -> Result<()> {
         handler_socialize_loss::process(ctx, liquidity_amount)
     }
 
+    pub fn update_obligation_borrow_factor_multiplier(
+        ctx: Context<UpdateObligationBorrowFactorMultiplier>,
+        borrow_factor_multiplier_bps: u64,
+    ) -> Result<()> {
+        handler_update_obligation_borrow_factor_multiplier::process(
+            ctx,
+            borrow_factor_multiplier_bps,
+        )
+    }
+
+    pub fn update_obligation_borrow_factor_discount_whitelist(
+        ctx: Context<UpdateObligationBorrowFactorDiscountWhitelist>,
+        whitelisted: bool,
+    ) -> Result<()> {
+        handler_update_obligation_borrow_factor_discount_whitelist::process(ctx, whitelisted)
+    }
+
     pub fn withdraw_protocol_fee(ctx: Context<WithdrawProtocolFees>, amount: u64) -> Result<()> {
         handler_withdraw_protocol_fees::process(ctx, amount)
     }
 
+    pub fn withdraw_protocol_fee_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawProtocolFeesBatch<'info>>,
+    ) -> Result<()> {
+        handler_withdraw_protocol_fees_batch::process(ctx)
+    }
+
     #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
     pub fn refresh_reserve(ctx: Context<RefreshReserve>) -> Result<()> {
         handler_refresh_reserve::process(ctx)
@@ -111,10 +181,30 @@ pub mod kamino_lending {
         handler_redeem_reserve_collateral::process(ctx, collateral_amount)
     }
 
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn compound_reserve_collateral_yield(
+        ctx: Context<CompoundReserveCollateralYield>,
+        collateral_amount: u64,
+        principal_liquidity_amount: u64,
+    ) -> Result<()> {
+        handler_compound_reserve_collateral_yield::process(
+            ctx,
+            collateral_amount,
+            principal_liquidity_amount,
+        )
+    }
+
     pub fn init_obligation(ctx: Context<InitObligation>, args: InitObligationArgs) -> Result<()> {
         handler_init_obligation::process(ctx, args)
     }
 
+    pub fn migrate_obligation_seeds<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateObligationSeeds<'info>>,
+        args: InitObligationArgs,
+    ) -> Result<()> {
+        handler_migrate_obligation_seeds::process(ctx, args)
+    }
+
     pub fn init_obligation_farms_for_reserve(
         ctx: Context<InitObligationFarmsForReserve>,
         mode: u8,
@@ -122,6 +212,13 @@ pub mod kamino_lending {
         handler_init_obligation_farms_for_reserve::process(ctx, mode)
     }
 
+    pub fn init_obligation_farms_for_reserves_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitObligationFarmsForReservesBatch<'info>>,
+        modes: Vec<u8>,
+    ) -> Result<()> {
+        handler_init_obligation_farms_for_reserves_batch::process(ctx, modes)
+    }
+
     #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
     pub fn refresh_obligation_farms_for_reserve(
         ctx: Context<RefreshObligationFarmsForReserve>,
@@ -135,6 +232,16 @@ pub mod kamino_lending {
         handler_refresh_obligation::process(ctx)
     }
 
+    pub fn get_obligation_snapshot(ctx: Context<GetObligationSnapshot>) -> Result<()> {
+        handler_get_obligation_snapshot::process(ctx)
+    }
+
+    pub fn get_reserve_vault_balance_snapshot(
+        ctx: Context<GetReserveVaultBalanceSnapshot>,
+    ) -> Result<()> {
+        handler_get_reserve_vault_balance_snapshot::process(ctx)
+    }
+
     #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
     pub fn deposit_obligation_collateral(
         ctx: Context<DepositObligationCollateral>,
@@ -156,7 +263,26 @@ pub mod kamino_lending {
         ctx: Context<'_, '_, '_, 'info, BorrowObligationLiquidity<'info>>,
         liquidity_amount: u64,
     ) -> Result<()> {
-        handler_borrow_obligation_liquidity::process(ctx, liquidity_amount)
+        handler_borrow_obligation_liquidity::process(ctx, liquidity_amount, None)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn borrow_obligation_liquidity_v2<'info>(
+        ctx: Context<'_, '_, '_, 'info, BorrowObligationLiquidity<'info>>,
+        liquidity_amount: u64,
+        max_borrow_fee: u64,
+    ) -> Result<()> {
+        handler_borrow_obligation_liquidity::process(ctx, liquidity_amount, Some(max_borrow_fee))
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn deposit_and_borrow<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositAndBorrow<'info>>,
+        liquidity_amount: u64,
+        borrow_amount: u64,
+        max_borrow_fee: u64,
+    ) -> Result<()> {
+        handler_deposit_and_borrow::process(ctx, liquidity_amount, borrow_amount, max_borrow_fee)
     }
 
     #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
@@ -183,6 +309,20 @@ pub mod kamino_lending {
         handler_withdraw_obligation_collateral_and_redeem_reserve_collateral::process(
             ctx,
             collateral_amount,
+            None,
+        )
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn withdraw_obligation_collateral_and_redeem_reserve_collateral_v2(
+        ctx: Context<WithdrawObligationCollateralAndRedeemReserveCollateral>,
+        collateral_amount: u64,
+        min_liquidity_received: u64,
+    ) -> Result<()> {
+        handler_withdraw_obligation_collateral_and_redeem_reserve_collateral::process(
+            ctx,
+            collateral_amount,
+            Some(min_liquidity_received),
         )
     }
 
@@ -198,6 +338,24 @@ pub mod kamino_lending {
             liquidity_amount,
             min_acceptable_received_liquidity_amount,
             max_allowed_ltv_override_percent,
+            false,
+        )
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn liquidate_obligation_and_redeem_reserve_collateral_v2(
+        ctx: Context<LiquidateObligationAndRedeemReserveCollateral>,
+        liquidity_amount: u64,
+        min_acceptable_received_liquidity_amount: u64,
+        max_allowed_ltv_override_percent: u64,
+        cap_to_redeemable_collateral: bool,
+    ) -> Result<()> {
+        handler_liquidate_obligation_and_redeem_reserve_collateral::process(
+            ctx,
+            liquidity_amount,
+            min_acceptable_received_liquidity_amount,
+            max_allowed_ltv_override_percent,
+            cap_to_redeemable_collateral,
         )
     }
 
@@ -230,6 +388,36 @@ pub mod kamino_lending {
         handler_request_elevation_group::process(ctx, elevation_group)
     }
 
+    pub fn update_obligation_self_borrow_limit(
+        ctx: Context<UpdateObligationSelfBorrowLimit>,
+        self_borrow_limit_value: u64,
+    ) -> Result<()> {
+        handler_update_obligation_self_borrow_limit::process(ctx, self_borrow_limit_value)
+    }
+
+    pub fn update_obligation_isolated_mode(
+        ctx: Context<UpdateObligationIsolatedMode>,
+        isolated_mode: u8,
+    ) -> Result<()> {
+        handler_update_obligation_isolated_mode::process(ctx, isolated_mode)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn update_obligation_collateral_supply_only(
+        ctx: Context<UpdateObligationCollateralSupplyOnly>,
+        supply_only: bool,
+    ) -> Result<()> {
+        handler_update_obligation_collateral_supply_only::process(ctx, supply_only)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn update_obligation_collateral_protected(
+        ctx: Context<UpdateObligationCollateralProtected>,
+        protected: bool,
+    ) -> Result<()> {
+        handler_update_obligation_collateral_protected::process(ctx, protected)
+    }
+
     pub fn init_referrer_token_state(
         ctx: Context<InitReferrerTokenState>,
         referrer: Pubkey,
@@ -249,6 +437,23 @@ pub mod kamino_lending {
         handler_withdraw_referrer_fees::process(ctx)
     }
 
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn claim_supply_referrer_fees(ctx: Context<ClaimSupplyReferrerFees>) -> Result<()> {
+        handler_claim_supply_referrer_fees::process(ctx)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn restake_referrer_fees(ctx: Context<RestakeReferrerFees>) -> Result<()> {
+        handler_restake_referrer_fees::process(ctx)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn withdraw_referrer_fees_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawReferrerFeesBatch<'info>>,
+    ) -> Result<()> {
+        handler_withdraw_referrer_fees_batch::process(ctx)
+    }
+
     pub fn init_referrer_state_and_short_url(
         ctx: Context<InitReferrerStateAndShortUrl>,
         short_url: String,
@@ -504,6 +709,48 @@ pub enum LendingError {
     DepositDisabledOutsideElevationGroup,
     #[msg("Cannot calculate referral amount due to slots mismatch")]
     CannotCalculateReferralAmountDueToSlotsMismatch,
+    #[msg("Liquidator is not allowed to liquidate this reserve")]
+    LiquidatorNotAllowed,
+    #[msg("New oracle price diverges too much from the stored reserve price")]
+    OracleRotationPriceDivergence,
+    #[msg("Borrow would exceed the obligation's self-imposed borrow limit")]
+    ObligationSelfBorrowLimitExceeded,
+    #[msg("Obligation in isolated mode can only have one collateral and one debt")]
+    ObligationIsolatedModeViolation,
+    #[msg("Borrow fees exceed the maximum accepted by the borrower")]
+    BorrowFeeExceedsMax,
+    #[msg("Reserve still has liquidity or borrows and cannot be made obsolete")]
+    ReserveNotEmpty,
+    #[msg("Borrow amount is below the reserve minimum borrow amount")]
+    BorrowAmountBelowMinimum,
+    #[msg("Obligation still has deposits or borrows")]
+    ObligationNotEmpty,
+    #[msg("Obligation holds too many obsolete reserves, repay or withdraw them first")]
+    ObligationTooManyObsoleteReserves,
+    #[msg("Liquidation repay value is below the market minimum")]
+    LiquidationRepayValueTooSmall,
+    #[msg("Redeemed liquidity is below the minimum expected amount")]
+    RedeemedLiquidityTooSmall,
+    #[msg("Hidden reserve cannot be used to open new obligation positions")]
+    ReserveHidden,
+    #[msg("Obligation deposit exceeds the reserve max deposit per obligation")]
+    ObligationDepositLimitExceeded,
+    #[msg("Obligation was liquidated too recently")]
+    LiquidationCooldownActive,
+    #[msg("Reserve is disabled for usage as collateral")]
+    DepositDisabledAsCollateral,
+    #[msg("Liquidation value exceeds the maximum allowed in a single instruction")]
+    LiquidationValueTooLarge,
+    #[msg("Reserve liquidity does not cover its fees and outstanding collateral")]
+    ReserveInsolvent,
+    #[msg("Insufficient host fees to withdraw or no liquidity available")]
+    InsufficientHostFeesToWithdraw,
+    #[msg(
+        "Farm state is not delegated to the lending market or its mint does not match the reserve"
+    )]
+    InvalidFarmForReserve,
+    #[msg("Liquidation must prioritize the collateral with the highest value")]
+    LiquidationHighestValuePriority,
 }
 
 pub type LendingResult<T = ()> = std::result::Result<T, LendingError>;
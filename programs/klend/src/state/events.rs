@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct CumulativeBorrowRateSnapshot {
+    pub reserve: Pubkey,
+    pub slot: u64,
+    pub cumulative_borrow_rate_bsf: [u64; 4],
+}
+
+#[event]
+pub struct ReserveStatusChanged {
+    pub reserve: Pubkey,
+    pub slot: u64,
+    pub previous_status: u8,
+    pub new_status: u8,
+    pub available_amount: u64,
+    pub borrowed_amount_sf: u128,
+}
+
+#[event]
+pub struct ObligationValueDrift {
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+    pub slot: u64,
+    pub slots_elapsed: u64,
+    pub previous_deposited_value_sf: u128,
+    pub deposited_value_sf: u128,
+    pub previous_borrow_factor_adjusted_debt_value_sf: u128,
+    pub borrow_factor_adjusted_debt_value_sf: u128,
+}
@@ -32,17 +32,27 @@ bitflags! {
         const TWAP_AGE_CHECKED =    0b_0000_1000;
         const HEURISTIC_CHECKED =   0b_0001_0000;
         const PRICE_USAGE_ALLOWED = 0b_0010_0000;
+        const LAST_VALID_PRICE_ALLOWED = 0b_0100_0000;
+        const TWAP_VERIFIED =       0b_1000_0000;
     }
 }
 
 impl PriceStatusFlags {
-    pub const ALL_CHECKS: PriceStatusFlags = PriceStatusFlags::all();
+    pub const ALL_CHECKS: PriceStatusFlags = PriceStatusFlags::PRICE_LOADED
+        .union(PriceStatusFlags::PRICE_AGE_CHECKED)
+        .union(PriceStatusFlags::TWAP_CHECKED)
+        .union(PriceStatusFlags::TWAP_AGE_CHECKED)
+        .union(PriceStatusFlags::HEURISTIC_CHECKED)
+        .union(PriceStatusFlags::PRICE_USAGE_ALLOWED);
 
     pub const NONE: PriceStatusFlags = PriceStatusFlags::empty();
 
     pub const LIQUIDATION_CHECKS: PriceStatusFlags = PriceStatusFlags::PRICE_LOADED
         .union(PriceStatusFlags::PRICE_AGE_CHECKED)
         .union(PriceStatusFlags::PRICE_USAGE_ALLOWED);
+
+    pub const STRICT_BORROW_CHECKS: PriceStatusFlags =
+        PriceStatusFlags::ALL_CHECKS.union(PriceStatusFlags::TWAP_VERIFIED);
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
@@ -92,6 +102,10 @@ impl LastUpdate {
         self.stale = true as u8;
     }
 
+    pub fn is_marked_stale(&self) -> bool {
+        self.stale != false as u8
+    }
+
     pub fn is_stale(&self, slot: Slot, min_price_status: PriceStatusFlags) -> Result<bool> {
         let is_price_status_ok = self.get_price_status().contains(min_price_status);
         Ok(self.stale != (false as u8)
@@ -11,12 +11,19 @@ use crate::{
     utils::{
         CLOSE_TO_INSOLVENCY_RISKY_LTV, ELEVATION_GROUP_NONE, GLOBAL_ALLOWED_BORROW_VALUE,
         GLOBAL_UNHEALTHY_BORROW_VALUE, LENDING_MARKET_SIZE, LIQUIDATION_CLOSE_FACTOR,
-        LIQUIDATION_CLOSE_VALUE, MAX_LIQUIDATABLE_VALUE_AT_ONCE, MIN_NET_VALUE_IN_OBLIGATION,
-        PROGRAM_VERSION,
+        LIQUIDATION_CLOSE_VALUE, MAX_LIQUIDATABLE_VALUE_AT_ONCE,
+        MAX_NUM_ELEVATION_GROUPS_PER_RESERVE, MIN_NET_VALUE_IN_OBLIGATION, PROGRAM_VERSION,
     },
     LendingError,
 };
 
+pub const OBLIGATION_VALUE_DRIFT_CHECK_DISABLED: u8 = 0;
+pub const OBLIGATION_VALUE_DRIFT_CHECK_WARN: u8 = 1;
+pub const OBLIGATION_VALUE_DRIFT_CHECK_STRICT: u8 = 2;
+
+pub const LIQUIDATION_COLLATERAL_PRIORITY_LOWEST_LTV: u8 = 0;
+pub const LIQUIDATION_COLLATERAL_PRIORITY_HIGHEST_VALUE: u8 = 1;
+
 static_assertions::const_assert_eq!(LENDING_MARKET_SIZE, std::mem::size_of::<LendingMarket>());
 static_assertions::const_assert_eq!(0, std::mem::size_of::<LendingMarket>() % 8);
 #[derive(PartialEq, Eq, Derivative)]
@@ -86,17 +93,60 @@ pub struct LendingMarket {
     #[cfg_attr(feature = "serde", serde(with = "serde_utf_string", default))]
     pub name: [u8; 32],
 
+    pub max_price_age_divergence_secs: u64,
+
+    #[cfg_attr(feature = "serde", serde(with = "serde_bool_u8"))]
+    pub cumulative_borrow_rate_events_enabled: u8,
+
+    pub max_obsolete_reserves_per_obligation: u8,
+
+    pub liquidation_collateral_priority: u8,
+
+    /// 0 means a reserve can join as many elevation groups as its config allows.
+    pub max_elevation_groups_per_reserve: u8,
+
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_deserializing, skip_serializing, default)
+    )]
+    #[derivative(Debug = "ignore")]
+    pub reserved2: [u8; 4],
+
+    pub min_liquidation_repay_value: u64,
+
+    pub liquidation_bonus_boost_bps: u16,
+
+    pub zero_ltv_collateral_withdrawal_first: u8,
+
+    pub min_elevation_group_ltv_spread_pct: u8,
+
+    pub strict_borrow_price_checks: u8,
+
+    pub obligation_value_drift_check_mode: u8,
+
+    pub min_protocol_take_rate_pct: u8,
+    /// 0 means no ceiling below 100%.
+    pub max_protocol_take_rate_pct: u8,
+
+    pub min_slots_between_liquidations: u64,
+
+    pub max_liquidation_value_per_ix: u64,
+
+    pub obligation_value_drift_tolerance_bps: u64,
+
+    pub min_borrow_health_buffer_bps: u64,
+
     #[cfg_attr(
         feature = "serde",
-        serde(skip_deserializing, skip_serializing, default = "default_padding_173")
+        serde(skip_deserializing, skip_serializing, default = "default_padding_165")
     )]
     #[derivative(Debug = "ignore")]
-    pub padding1: [u64; 173],
+    pub padding1: [u64; 165],
 }
 
 #[cfg(feature = "serde")]
-fn default_padding_173() -> [u64; 173] {
-    [0; 173]
+fn default_padding_165() -> [u64; 165] {
+    [0; 165]
 }
 
 #[cfg(feature = "serde")]
@@ -130,12 +180,71 @@ impl Default for LendingMarket {
             elevation_group_padding: [0; 90],
             min_net_value_in_obligation_sf: MIN_NET_VALUE_IN_OBLIGATION.to_bits(),
             name: [0; 32],
-            padding1: [0; 173],
+            max_price_age_divergence_secs: 0,
+            cumulative_borrow_rate_events_enabled: 0,
+            max_obsolete_reserves_per_obligation: 0,
+            liquidation_collateral_priority: 0,
+            max_elevation_groups_per_reserve: 0,
+            reserved2: [0; 4],
+            min_liquidation_repay_value: 0,
+            liquidation_bonus_boost_bps: 0,
+            zero_ltv_collateral_withdrawal_first: 0,
+            min_elevation_group_ltv_spread_pct: 0,
+            strict_borrow_price_checks: 0,
+            obligation_value_drift_check_mode: 0,
+            min_protocol_take_rate_pct: 0,
+            max_protocol_take_rate_pct: 0,
+            min_slots_between_liquidations: 0,
+            max_liquidation_value_per_ix: 0,
+            obligation_value_drift_tolerance_bps: 0,
+            min_borrow_health_buffer_bps: 0,
+            padding1: [0; 165],
         }
     }
 }
 
 impl LendingMarket {
+    pub fn is_cumulative_borrow_rate_events_enabled(&self) -> bool {
+        self.cumulative_borrow_rate_events_enabled != false as u8
+    }
+
+    pub fn is_strict_borrow_price_checks(&self) -> bool {
+        self.strict_borrow_price_checks != false as u8
+    }
+
+    pub fn is_obligation_value_drift_check_enabled(&self) -> bool {
+        self.obligation_value_drift_check_mode != OBLIGATION_VALUE_DRIFT_CHECK_DISABLED
+    }
+
+    pub fn is_obligation_value_drift_check_strict(&self) -> bool {
+        self.obligation_value_drift_check_mode == OBLIGATION_VALUE_DRIFT_CHECK_STRICT
+    }
+
+    pub fn is_liquidation_collateral_priority_highest_value(&self) -> bool {
+        self.liquidation_collateral_priority == LIQUIDATION_COLLATERAL_PRIORITY_HIGHEST_VALUE
+    }
+
+    pub fn max_elevation_groups_per_reserve(&self) -> u8 {
+        if self.max_elevation_groups_per_reserve == 0 {
+            MAX_NUM_ELEVATION_GROUPS_PER_RESERVE
+        } else {
+            self.max_elevation_groups_per_reserve
+        }
+    }
+
+    pub fn protocol_take_rate_pct_bounds(&self) -> (u8, u8) {
+        let max_protocol_take_rate_pct = if self.max_protocol_take_rate_pct == 0 {
+            100
+        } else {
+            self.max_protocol_take_rate_pct
+        };
+        (self.min_protocol_take_rate_pct, max_protocol_take_rate_pct)
+    }
+
+    pub fn is_zero_ltv_collateral_withdrawal_first(&self) -> bool {
+        self.zero_ltv_collateral_withdrawal_first != false as u8
+    }
+
     pub fn init(&mut self, params: InitLendingMarketParams) {
         *self = Self::default();
         self.version = PROGRAM_VERSION as u64;
@@ -1,6 +1,10 @@
 use std::cmp::{max, min, Ordering};
 
-use anchor_lang::{err, prelude::msg, Result};
+use anchor_lang::{
+    err,
+    prelude::{msg, Pubkey},
+    Result,
+};
 use solana_program::clock::Slot;
 
 use crate::{
@@ -11,8 +15,8 @@ use crate::{
         ELEVATION_GROUP_NONE, MIN_AUTODELEVERAGE_BONUS_BPS,
     },
     xmsg, CalculateLiquidationResult, LendingError, LendingMarket, LendingResult,
-    LiquidationParams, Obligation, ObligationCollateral, ObligationLiquidity, Reserve,
-    ReserveConfig,
+    LiquidationParams, LiquidationReason, Obligation, ObligationCollateral, ObligationLiquidity,
+    Reserve, ReserveConfig,
 };
 
 pub fn max_liquidatable_borrowed_amount(
@@ -61,7 +65,7 @@ pub fn calculate_liquidation(
     collateral: &ObligationCollateral,
     current_slot: Slot,
     is_debt_reserve_highest_borrow_factor: bool,
-    is_collateral_reserve_lowest_liquidation_ltv: bool,
+    is_collateral_reserve_liquidation_priority: bool,
     max_allowed_ltv_override_pct_opt: Option<u64>,
 ) -> Result<CalculateLiquidationResult> {
     if obligation.deposited_value_sf == 0 {
@@ -72,6 +76,7 @@ pub fn calculate_liquidation(
     let LiquidationParams {
         user_ltv,
         liquidation_bonus_rate,
+        liquidation_reason,
     } = get_liquidation_params(
         lending_market,
         collateral_reserve,
@@ -79,7 +84,7 @@ pub fn calculate_liquidation(
         obligation,
         current_slot,
         is_debt_reserve_highest_borrow_factor,
-        is_collateral_reserve_lowest_liquidation_ltv,
+        is_collateral_reserve_liquidation_priority,
         max_allowed_ltv_override_pct_opt,
     )?;
 
@@ -93,32 +98,63 @@ pub fn calculate_liquidation(
         Fraction::from_num(debt_amount_to_liquidate).min(borrowed_amount_f);
 
     let is_below_min_full_liquidation_value_threshold =
-        borrowed_value_f < lending_market.min_full_liquidation_value_threshold;
+        is_full_liquidation_mandatory(lending_market, liquidity);
 
     let debt_liquidation_amount_f = if is_below_min_full_liquidation_value_threshold {
         borrowed_amount_f
     } else {
-        max_liquidatable_borrowed_amount(
+        let max_liquidatable_amount_f = max_liquidatable_borrowed_amount(
             obligation,
             lending_market.liquidation_max_debt_close_factor_pct,
             lending_market.max_liquidatable_debt_market_value_at_once,
             liquidity,
             user_ltv,
             lending_market.insolvency_risk_unhealthy_ltv_pct,
-        )
-        .min(debt_amount_to_liquidate)
+        );
+        let debt_liquidation_amount_f = max_liquidatable_amount_f.min(debt_amount_to_liquidate);
+
+        check_min_liquidation_repay_value(
+            lending_market,
+            borrowed_value_f * (debt_liquidation_amount_f / borrowed_amount_f),
+            borrowed_value_f * (max_liquidatable_amount_f / borrowed_amount_f),
+        )?;
+
+        debt_liquidation_amount_f
     };
 
     let liquidation_ratio = debt_liquidation_amount_f / borrowed_amount_f;
 
     let total_liquidation_value_including_bonus = borrowed_value_f * liquidation_ratio * bonus_rate;
 
-    let (settle_amount, repay_amount, withdraw_amount) = calculate_liquidation_amounts(
-        total_liquidation_value_including_bonus,
-        collateral,
-        debt_liquidation_amount_f,
-        is_below_min_full_liquidation_value_threshold,
-    );
+    if lending_market.max_liquidation_value_per_ix > 0
+        && total_liquidation_value_including_bonus
+            > Fraction::from(lending_market.max_liquidation_value_per_ix)
+    {
+        xmsg!(
+            "Liquidation value {} exceeds the maximum liquidation value per instruction {}",
+            total_liquidation_value_including_bonus.to_display(),
+            lending_market.max_liquidation_value_per_ix
+        );
+        return err!(LendingError::LiquidationValueTooLarge);
+    }
+
+    let (settle_amount, repay_amount, withdraw_amount) =
+        if collateral_reserve.liquidity.mint_pubkey == debt_reserve.liquidity.mint_pubkey {
+            calculate_same_asset_liquidation_amounts(
+                collateral_reserve,
+                collateral,
+                debt_liquidation_amount_f,
+                bonus_rate,
+                is_below_min_full_liquidation_value_threshold,
+            )?
+        } else {
+            calculate_liquidation_amounts(
+                total_liquidation_value_including_bonus,
+                collateral,
+                debt_liquidation_amount_f,
+                is_below_min_full_liquidation_value_threshold,
+            )
+        };
 
     xmsg!(
         "Obligation is liquidated with liquidation bonus: {} bps, liquidation amount (rounded): {}",
@@ -131,9 +167,57 @@ pub fn calculate_liquidation(
         repay_amount,
         withdraw_amount,
         liquidation_bonus_rate,
+        liquidation_reason,
     })
 }
 
+fn is_full_liquidation_mandatory(
+    lending_market: &LendingMarket,
+    liquidity: &ObligationLiquidity,
+) -> bool {
+    Fraction::from_bits(liquidity.market_value_sf)
+        < lending_market.min_full_liquidation_value_threshold
+}
+
+/// Repay amount a liquidator must provide to liquidate the given borrow when its value is below
+/// `min_full_liquidation_value_threshold`, `None` when partial liquidations are allowed.
+/// The obligation must be refreshed.
+pub fn get_full_liquidation_required_repay_amount(
+    lending_market: &LendingMarket,
+    obligation: &Obligation,
+    debt_reserve: Pubkey,
+) -> Result<Option<u64>> {
+    let (liquidity, _) = obligation.find_liquidity_in_borrows(debt_reserve)?;
+
+    if !is_full_liquidation_mandatory(lending_market, liquidity) {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        Fraction::from_bits(liquidity.borrowed_amount_sf).to_ceil(),
+    ))
+}
+
+fn check_min_liquidation_repay_value(
+    lending_market: &LendingMarket,
+    repay_value: Fraction,
+    max_liquidatable_value: Fraction,
+) -> Result<()> {
+    let min_repay_value =
+        Fraction::from(lending_market.min_liquidation_repay_value).min(max_liquidatable_value);
+
+    if repay_value < min_repay_value {
+        xmsg!(
+            "Liquidation repay value {} is below the minimum {}",
+            repay_value.to_display(),
+            min_repay_value.to_display()
+        );
+        return err!(LendingError::LiquidationRepayValueTooSmall);
+    }
+
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn get_liquidation_params(
     lending_market: &LendingMarket,
@@ -142,7 +226,7 @@ pub fn get_liquidation_params(
     obligation: &Obligation,
     slot: Slot,
     is_debt_reserve_highest_borrow_factor: bool,
-    is_collateral_reserve_lowest_liquidation_ltv: bool,
+    is_collateral_reserve_liquidation_priority: bool,
     max_allowed_ltv_override_pct_opt: Option<u64>,
 ) -> Result<LiquidationParams> {
     if let Some(params) = check_liquidate_obligation(
@@ -157,7 +241,11 @@ pub fn get_liquidation_params(
             return err!(LendingError::LiquidationBorrowFactorPriority,);
         }
 
-        if !is_collateral_reserve_lowest_liquidation_ltv {
+        if !is_collateral_reserve_liquidation_priority {
+            if lending_market.is_liquidation_collateral_priority_highest_value() {
+                xmsg!("Collateral reserve is not the highest value reserve, obligation cannot be liquidated");
+                return err!(LendingError::LiquidationHighestValuePriority);
+            }
             xmsg!(
                 "Collateral reserve is not the lowest LTV reserve, obligation cannot be liquidated"
             );
@@ -198,7 +286,6 @@ pub fn check_liquidate_obligation(
     max_allowed_ltv_override_pct_opt: Option<u64>,
 ) -> Option<LiquidationParams> {
     let user_ltv = obligation.loan_to_value();
-    let user_no_bf_ltv = obligation.no_bf_loan_to_value();
     let max_allowed_ltv_user = obligation.unhealthy_loan_to_value();
     let max_allowed_ltv_override_opt = max_allowed_ltv_override_pct_opt.map(Fraction::from_percent);
     let max_allowed_ltv = max_allowed_ltv_override_opt.unwrap_or(max_allowed_ltv_user);
@@ -213,45 +300,98 @@ pub fn check_liquidate_obligation(
             max_allowed_ltv_override_pct_opt,
         );
 
-        let emode_max_liquidation_bonus_bps = get_emode_max_liquidation_bonus(
-            lending_market,
-            &collateral_reserve.config,
-            &debt_reserve.config,
-            obligation,
-        );
-
         return Some(LiquidationParams {
             user_ltv,
-            liquidation_bonus_rate: calculate_liquidation_bonus(
-                &collateral_reserve.config,
-                &debt_reserve.config,
+            liquidation_bonus_rate: calculate_liquidation_bonus_for_obligation(
+                lending_market,
+                collateral_reserve,
+                debt_reserve,
+                obligation,
                 max_allowed_ltv,
-                user_ltv,
-                user_no_bf_ltv,
-                emode_max_liquidation_bonus_bps,
             )
             .unwrap(),
+            liquidation_reason: LiquidationReason::LtvExceeded,
         });
     }
     None
 }
 
-fn get_emode_max_liquidation_bonus(
+/// Partial liquidations of an obligation are spaced by the market `min_slots_between_liquidations`,
+/// except above the insolvency risk LTV where the obligation must be closed out without delay.
+pub fn is_liquidation_cooldown_active(
+    lending_market: &LendingMarket,
+    obligation: &Obligation,
+    slot: Slot,
+) -> bool {
+    lending_market.min_slots_between_liquidations > 0
+        && obligation.last_liquidation_slot > 0
+        && slot
+            < obligation
+                .last_liquidation_slot
+                .saturating_add(lending_market.min_slots_between_liquidations)
+        && obligation.loan_to_value()
+            <= Fraction::from_percent(lending_market.insolvency_risk_unhealthy_ltv_pct)
+}
+
+pub fn estimate_liquidation_bonus(
+    lending_market: &LendingMarket,
+    collateral_reserve: &Reserve,
+    debt_reserve: &Reserve,
+    obligation: &Obligation,
+    max_allowed_ltv_override_pct_opt: Option<u64>,
+) -> Result<Fraction> {
+    let max_allowed_ltv = max_allowed_ltv_override_pct_opt
+        .map(Fraction::from_percent)
+        .unwrap_or_else(|| obligation.unhealthy_loan_to_value());
+
+    calculate_liquidation_bonus_for_obligation(
+        lending_market,
+        collateral_reserve,
+        debt_reserve,
+        obligation,
+        max_allowed_ltv,
+    )
+}
+
+fn calculate_liquidation_bonus_for_obligation(
+    lending_market: &LendingMarket,
+    collateral_reserve: &Reserve,
+    debt_reserve: &Reserve,
+    obligation: &Obligation,
+    max_allowed_ltv: Fraction,
+) -> Result<Fraction> {
+    let emode_max_liquidation_bonus_bps = get_emode_max_liquidation_bonus(
+        lending_market,
+        &collateral_reserve.config,
+        &debt_reserve.config,
+        obligation.elevation_group,
+    );
+
+    calculate_liquidation_bonus(
+        &collateral_reserve.config,
+        &debt_reserve.config,
+        max_allowed_ltv,
+        obligation.loan_to_value(),
+        obligation.no_bf_loan_to_value(),
+        emode_max_liquidation_bonus_bps,
+        lending_market.liquidation_bonus_boost_bps,
+    )
+}
+
+pub fn get_emode_max_liquidation_bonus(
     lending_market: &LendingMarket,
     collateral_reserve: &ReserveConfig,
     debt_reserve: &ReserveConfig,
-    obligation: &Obligation,
+    elevation_group_id: u8,
 ) -> u16 {
-    if obligation.elevation_group != ELEVATION_GROUP_NONE
+    if elevation_group_id != ELEVATION_GROUP_NONE
         && collateral_reserve
             .elevation_groups
-            .contains(&obligation.elevation_group)
-        && debt_reserve
-            .elevation_groups
-            .contains(&obligation.elevation_group)
+            .contains(&elevation_group_id)
+        && debt_reserve.elevation_groups.contains(&elevation_group_id)
     {
         let elevation_group = lending_market
-            .get_elevation_group(obligation.elevation_group)
+            .get_elevation_group(elevation_group_id)
             .unwrap()
             .unwrap();
 
@@ -316,6 +456,63 @@ fn calculate_liquidation_amounts(
     }
 }
 
+fn calculate_same_asset_liquidation_amounts(
+    collateral_reserve: &Reserve,
+    collateral: &ObligationCollateral,
+    debt_liquidation_amount: Fraction,
+    bonus_rate: Fraction,
+    is_below_min_full_liquidation_value_threshold: bool,
+) -> Result<(Fraction, u64, u64)> {
+    let collateral_exchange_rate = collateral_reserve.collateral_exchange_rate()?;
+    let collateral_liquidity_amount = collateral_exchange_rate
+        .fraction_collateral_to_liquidity(Fraction::from(collateral.deposited_amount));
+    let total_liquidation_amount_including_bonus = debt_liquidation_amount * bonus_rate;
+
+    let amounts = match total_liquidation_amount_including_bonus.cmp(&collateral_liquidity_amount) {
+        Ordering::Greater => {
+            let repay_ratio =
+                collateral_liquidity_amount / total_liquidation_amount_including_bonus;
+
+            let settle_amount = if is_below_min_full_liquidation_value_threshold {
+                debt_liquidation_amount
+            } else {
+                debt_liquidation_amount * repay_ratio
+            };
+
+            (
+                settle_amount,
+                settle_amount.to_ceil(),
+                collateral.deposited_amount,
+            )
+        }
+        Ordering::Equal => (
+            debt_liquidation_amount,
+            debt_liquidation_amount.to_ceil(),
+            collateral.deposited_amount,
+        ),
+        Ordering::Less => {
+            let withdraw_amount_f = collateral_exchange_rate
+                .fraction_liquidity_to_collateral(total_liquidation_amount_including_bonus);
+
+            let withdraw_amount = if is_below_min_full_liquidation_value_threshold
+                && withdraw_amount_f < DUST_LAMPORT_THRESHOLD
+            {
+                DUST_LAMPORT_THRESHOLD
+            } else {
+                withdraw_amount_f.to_floor()
+            };
+
+            (
+                debt_liquidation_amount,
+                debt_liquidation_amount.to_ceil(),
+                min(withdraw_amount, collateral.deposited_amount),
+            )
+        }
+    };
+
+    Ok(amounts)
+}
+
 fn calculate_liquidation_bonus(
     collateral_reserve_config: &ReserveConfig,
     debt_reserve_config: &ReserveConfig,
@@ -323,6 +520,7 @@ fn calculate_liquidation_bonus(
     user_ltv: Fraction,
     user_no_bf_ltv: Fraction,
     emode_max_liquidation_bonus_bps: u16,
+    liquidation_bonus_boost_bps: u16,
 ) -> Result<Fraction> {
     let bad_debt_ltv = Fraction::ONE;
 
@@ -361,7 +559,8 @@ fn calculate_liquidation_bonus(
 
     let min_reserve_bonus = Fraction::from_bps(min_reserve_bonus_bps);
 
-    let min_bonus = max(min_reserve_bonus, unhealthy_factor);
+    let min_bonus =
+        max(min_reserve_bonus, unhealthy_factor) + Fraction::from_bps(liquidation_bonus_boost_bps);
 
     let collared_bonus = min(min_bonus, max_bonus);
 
@@ -433,6 +632,7 @@ fn get_autodeleverage_liquidation_params(
         Some(LiquidationParams {
             user_ltv,
             liquidation_bonus_rate: liquidation_bonus,
+            liquidation_reason: LiquidationReason::AutoDeleveraging,
         })
     } else {
         xmsg!("LTV is below the current auto-deleverage threshold: {user_ltv}/{autodeleverage_ltv_threshold}, slots since deleveraging started: {slots_since_deleveraging_started}, LTV reduction: {ltv_reduction_bps}", );
@@ -559,3 +759,44 @@ pub fn calculate_protocol_liquidation_fee(
 
     max(protocol_fee, 1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obligation_at_ltv_pct(ltv_pct: u64) -> Obligation {
+        Obligation {
+            deposited_value_sf: Fraction::from_num(100).to_bits(),
+            borrow_factor_adjusted_debt_value_sf: Fraction::from_num(ltv_pct).to_bits(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn liquidation_cooldown_spaces_partial_liquidations() {
+        let market = LendingMarket {
+            min_slots_between_liquidations: 100,
+            insolvency_risk_unhealthy_ltv_pct: 95,
+            ..Default::default()
+        };
+        let mut obligation = obligation_at_ltv_pct(90);
+        assert!(!is_liquidation_cooldown_active(&market, &obligation, 1_050));
+
+        obligation.last_liquidation_slot = 1_000;
+        assert!(is_liquidation_cooldown_active(&market, &obligation, 1_050));
+        assert!(!is_liquidation_cooldown_active(&market, &obligation, 1_100));
+    }
+
+    #[test]
+    fn liquidation_cooldown_does_not_apply_above_insolvency_risk_ltv() {
+        let market = LendingMarket {
+            min_slots_between_liquidations: 100,
+            insolvency_risk_unhealthy_ltv_pct: 95,
+            ..Default::default()
+        };
+        let mut obligation = obligation_at_ltv_pct(96);
+        obligation.last_liquidation_slot = 1_000;
+
+        assert!(!is_liquidation_cooldown_active(&market, &obligation, 1_050));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod events;
 pub mod last_update;
 pub mod lending_market;
 pub mod liquidation_operations;
@@ -9,6 +10,7 @@ pub mod token_info;
 pub mod types;
 
 use anchor_lang::prelude::*;
+pub use events::*;
 pub use last_update::*;
 pub use lending_market::*;
 pub use nested_accounts::*;
@@ -132,6 +134,30 @@ pub enum UpdateConfigMode {
     UpdateBorrowLimitOutsideElevationGroup = 45,
     UpdateBorrowLimitsInElevationGroupAgainstThisReserve = 46,
     UpdateHostFixedInterestRateBps = 47,
+    UpdateLimitsInQuoteValue = 48,
+    UpdateLiquidatorAllowlist = 49,
+    UpdateProtocolTakeRateBands = 50,
+    UpdateTokenInfoPriceMaxAgeGrace = 51,
+    UpdateTokenInfoPriceFeedPriority = 52,
+    UpdateMinBorrowAmount = 53,
+    UpdateFeesFlashLoanProtocolFee = 54,
+    UpdateBorrowCollateralHaircutBps = 55,
+    UpdateMaxDepositPerObligation = 56,
+    UpdateDisableUsageAsCollateral = 57,
+    UpdateMinBorrowRateBps = 58,
+    UpdateMaxBorrowRateBps = 59,
+    UpdateMaxReferrerFees = 60,
+    UpdateSupplyReferrer = 61,
+    UpdateSupplyReferralFeeBps = 62,
+    UpdateElevationGroupOriginationFeeDiscountBps = 63,
+    UpdateMaxConsecutiveStalePriceRefreshes = 64,
+    UpdateStalePriceBorrowingDisabled = 65,
+    UpdateForgiveProtocolFeeDust = 66,
+    UpdateHostFeeReceiver = 67,
+    UpdateFeesRedeemFee = 68,
+    UpdateMaxInterestAccrualSlots = 69,
+    UpdateTokenInfoQuoteCurrency = 70,
+    UpdateMaxOracleRotationPriceDivergenceBps = 71,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Eq, Clone, Debug)]
@@ -215,6 +241,23 @@ pub enum UpdateLendingMarketMode {
     UpdateMinValueSkipPriorityLiqCheck = 16,
     UpdatePaddingFields = 17,
     UpdateName = 18,
+    UpdateMaxPriceAgeDivergenceSecs = 19,
+    UpdateCumulativeBorrowRateEventsEnabled = 20,
+    UpdateMaxObsoleteReservesPerObligation = 21,
+    UpdateMinLiquidationRepayValue = 22,
+    UpdateLiquidationBonusBoostBps = 23,
+    UpdateMinSlotsBetweenLiquidations = 24,
+    UpdateZeroLtvCollateralWithdrawalFirst = 25,
+    UpdateMaxLiquidationValuePerIx = 26,
+    UpdateMinElevationGroupLtvSpreadPct = 27,
+    UpdateStrictBorrowPriceChecks = 28,
+    UpdateObligationValueDriftCheckMode = 29,
+    UpdateObligationValueDriftToleranceBps = 30,
+    UpdateMinBorrowHealthBufferBps = 31,
+    UpdateMinProtocolTakeRatePct = 32,
+    UpdateMaxProtocolTakeRatePct = 33,
+    UpdateLiquidationCollateralPriority = 34,
+    UpdateMaxElevationGroupsPerReserve = 35,
 }
 
 #[cfg(feature = "serde")]
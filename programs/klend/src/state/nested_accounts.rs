@@ -35,6 +35,13 @@ pub struct DepositReserveLiquidityAndObligationCollateralAccounts<'info> {
     pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
 }
 
+#[derive(Accounts)]
+pub struct BorrowObligationLiquidityAccounts<'info> {
+    pub borrow_reserve: AccountLoader<'info, Reserve>,
+    pub borrow_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
 #[derive(Accounts)]
 pub struct WithdrawObligationCollateralAccounts<'info> {
     pub reserve_source_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
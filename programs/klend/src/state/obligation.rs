@@ -9,7 +9,7 @@ use derivative::Derivative;
 use super::LastUpdate;
 use crate::{
     utils::{BigFraction, Fraction, FractionExtra, ELEVATION_GROUP_NONE, OBLIGATION_SIZE, U256},
-    xmsg, AssetTier, BigFractionBytes, LendingError, LendingResult,
+    xmsg, AssetTier, BigFractionBytes, LendingError, LendingResult, Reserve, ReserveStatus,
 };
 
 static_assertions::const_assert_eq!(OBLIGATION_SIZE, std::mem::size_of::<Obligation>());
@@ -46,13 +46,31 @@ pub struct Obligation {
 
     pub borrowing_disabled: u8,
 
+    pub isolated_mode: u8,
+
+    pub has_zero_ltv_deposit: u8,
+
+    pub has_unprotected_lowest_liquidation_ltv_deposit: u8,
+
+    /// Set by the lending market owner, allows a borrow factor multiplier below 100%.
+    pub borrow_factor_discount_whitelisted: u8,
+
     #[derivative(Debug = "ignore")]
-    pub reserved: [u8; 7],
+    pub reserved: [u8; 3],
 
     pub highest_borrow_factor_pct: u64,
 
+    pub self_borrow_limit_value: u64,
+
+    pub last_liquidation_slot: u64,
+
+    /// Multiplier applied to the borrow factor of every borrow, set by the risk council.
+    /// 0 means unset and the reserves' borrow factors are used. Below 100% only for obligations
+    /// whitelisted by the lending market owner.
+    pub borrow_factor_multiplier_bps: u64,
+
     #[derivative(Debug = "ignore")]
-    pub padding_3: [u64; 126],
+    pub padding_3: [u64; 123],
 }
 
 impl Default for Obligation {
@@ -77,8 +95,15 @@ impl Default for Obligation {
             has_debt: 0,
             borrowing_disabled: 0,
             highest_borrow_factor_pct: 0,
-            reserved: [0; 7],
-            padding_3: [0; 126],
+            isolated_mode: 0,
+            has_zero_ltv_deposit: 0,
+            has_unprotected_lowest_liquidation_ltv_deposit: 0,
+            borrow_factor_discount_whitelisted: 0,
+            reserved: [0; 3],
+            self_borrow_limit_value: 0,
+            last_liquidation_slot: 0,
+            borrow_factor_multiplier_bps: 0,
+            padding_3: [0; 123],
             referrer: Pubkey::default(),
         }
     }
@@ -133,6 +158,12 @@ pub enum WithdrawResult {
     Partial,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ObligationCloseBlocker {
+    ActiveDeposits,
+    ActiveBorrows,
+}
+
 impl Obligation {
     pub const LEN: usize = 1784;
 
@@ -237,6 +268,7 @@ impl Obligation {
         &mut self,
         deposit_reserve: Pubkey,
         deposit_reserve_asset_tier: AssetTier,
+        deposit_reserve_status: ReserveStatus,
         init_function: impl FnOnce(&mut ObligationCollateral) -> Result<()>,
     ) -> Result<&mut ObligationCollateral> {
         if let Some(collateral_index) = self
@@ -250,6 +282,16 @@ impl Obligation {
             .iter()
             .position(|c| c.deposit_reserve == Pubkey::default())
         {
+            if deposit_reserve_status == ReserveStatus::Hidden {
+                xmsg!("Hidden reserve cannot be added as a new collateral");
+                return err!(LendingError::ReserveHidden);
+            }
+
+            if self.is_isolated_mode() && !self.deposits_empty() {
+                xmsg!("Obligation in isolated mode can only have one collateral");
+                return err!(LendingError::ObligationIsolatedModeViolation);
+            }
+
             let collateral = &mut self.deposits[collateral_index];
             *collateral = ObligationCollateral::new(deposit_reserve);
             self.deposits_asset_tiers[collateral_index] = deposit_reserve_asset_tier.into();
@@ -302,20 +344,75 @@ impl Obligation {
         Ok((&mut self.borrows[liquidity_index], liquidity_index))
     }
 
+    /// Borrow rate of the obligation's debt, averaged over its borrow reserves and weighted by the
+    /// market value of each borrow. Every borrow reserve of the obligation must be given exactly
+    /// once. The obligation must be refreshed.
+    pub fn calculate_weighted_average_borrow_rate<'a>(
+        &self,
+        borrow_reserves: impl IntoIterator<Item = (Pubkey, &'a Reserve)>,
+    ) -> Result<Fraction> {
+        let mut total_market_value_f = Fraction::ZERO;
+        let mut weighted_borrow_rate_f = Fraction::ZERO;
+        let mut is_borrow_included = [false; 5];
+
+        for (borrow_reserve_pk, borrow_reserve) in borrow_reserves {
+            let (liquidity, liquidity_index) = self.find_liquidity_in_borrows(borrow_reserve_pk)?;
+            if is_borrow_included[liquidity_index] {
+                xmsg!("Borrow reserve {} given more than once", borrow_reserve_pk);
+                return err!(LendingError::InvalidAccountInput);
+            }
+            is_borrow_included[liquidity_index] = true;
+
+            let market_value_f = Fraction::from_bits(liquidity.market_value_sf);
+
+            weighted_borrow_rate_f += market_value_f * borrow_reserve.current_borrow_rate()?;
+            total_market_value_f += market_value_f;
+        }
+
+        if self
+            .borrows
+            .iter()
+            .zip(is_borrow_included)
+            .any(|(liquidity, is_included)| {
+                liquidity.borrow_reserve != Pubkey::default() && !is_included
+            })
+        {
+            xmsg!("All the borrow reserves of the obligation must be given");
+            return err!(LendingError::InvalidAccountInput);
+        }
+
+        if total_market_value_f == Fraction::ZERO {
+            return Ok(Fraction::ZERO);
+        }
+
+        Ok(weighted_borrow_rate_f / total_market_value_f)
+    }
+
     pub fn find_or_add_liquidity_to_borrows(
         &mut self,
         borrow_reserve: Pubkey,
         cumulative_borrow_rate: BigFraction,
         borrow_reserve_asset_tier: AssetTier,
+        borrow_reserve_status: ReserveStatus,
     ) -> Result<(&mut ObligationLiquidity, usize)> {
         if let Some(liquidity_index) = self.find_liquidity_index_in_borrows(borrow_reserve) {
             Ok((&mut self.borrows[liquidity_index], liquidity_index))
-        } else if let Some((index, liquidity)) = self
+        } else if let Some(index) = self
             .borrows
-            .iter_mut()
-            .enumerate()
-            .find(|c| c.1.borrow_reserve == Pubkey::default())
+            .iter()
+            .position(|l| l.borrow_reserve == Pubkey::default())
         {
+            if borrow_reserve_status == ReserveStatus::Hidden {
+                xmsg!("Hidden reserve cannot be added as a new debt");
+                return err!(LendingError::ReserveHidden);
+            }
+
+            if self.is_isolated_mode() && !self.borrows_empty() {
+                xmsg!("Obligation in isolated mode can only have one debt");
+                return err!(LendingError::ObligationIsolatedModeViolation);
+            }
+
+            let liquidity = &mut self.borrows[index];
             *liquidity = ObligationLiquidity::new(borrow_reserve, cumulative_borrow_rate);
             self.borrows_asset_tiers[index] = borrow_reserve_asset_tier.into();
 
@@ -344,6 +441,21 @@ impl Obligation {
             .all(|l| l.borrow_reserve == Pubkey::default())
     }
 
+    /// Supply-only deposits are not collateral while other collateral backs the debt. Once only
+    /// supply-only deposits are left against an open debt, they count as collateral so that they
+    /// can be seized by liquidators instead of leaving bad debt.
+    pub fn is_supply_only_counted_as_collateral(&self) -> bool {
+        !self.borrows_empty()
+            && self
+                .deposits
+                .iter()
+                .all(|c| c.deposit_reserve == Pubkey::default() || c.is_supply_only())
+    }
+
+    pub fn is_counted_as_collateral(&self, collateral: &ObligationCollateral) -> bool {
+        !collateral.is_supply_only() || self.is_supply_only_counted_as_collateral()
+    }
+
     pub fn deposits_count(&self) -> usize {
         self.deposits
             .iter()
@@ -358,6 +470,39 @@ impl Obligation {
             .count()
     }
 
+    pub fn active_deposit_reserves(&self) -> Vec<Pubkey> {
+        self.deposits
+            .iter()
+            .filter(|c| c.deposit_reserve != Pubkey::default())
+            .map(|c| c.deposit_reserve)
+            .collect()
+    }
+
+    pub fn active_borrow_reserves(&self) -> Vec<Pubkey> {
+        self.borrows
+            .iter()
+            .filter(|l| l.borrow_reserve != Pubkey::default())
+            .map(|l| l.borrow_reserve)
+            .collect()
+    }
+
+    pub fn find_refresh_reserves_mismatch(&self, reserves: &[Pubkey]) -> Option<usize> {
+        let expected_reserves: Vec<Pubkey> = self
+            .active_deposit_reserves()
+            .into_iter()
+            .chain(self.active_borrow_reserves())
+            .collect();
+
+        expected_reserves
+            .iter()
+            .zip(reserves.iter())
+            .position(|(expected, actual)| expected != actual)
+            .or_else(|| {
+                (expected_reserves.len() != reserves.len())
+                    .then(|| expected_reserves.len().min(reserves.len()))
+            })
+    }
+
     pub fn get_deposit_asset_tiers(&self) -> Vec<AssetTier> {
         self.deposits
             .iter()
@@ -408,6 +553,49 @@ impl Obligation {
             self.has_debt = 1;
         }
     }
+
+    pub fn close_blocker(&self) -> Option<ObligationCloseBlocker> {
+        if !self.deposits_empty() {
+            Some(ObligationCloseBlocker::ActiveDeposits)
+        } else if !self.borrows_empty() {
+            Some(ObligationCloseBlocker::ActiveBorrows)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_closeable(&self) -> bool {
+        self.close_blocker().is_none()
+    }
+
+    pub fn is_isolated_mode(&self) -> bool {
+        self.isolated_mode != 0
+    }
+
+    pub fn has_zero_ltv_deposit(&self) -> bool {
+        self.has_zero_ltv_deposit != 0
+    }
+
+    pub fn is_borrow_factor_discount_whitelisted(&self) -> bool {
+        self.borrow_factor_discount_whitelisted != 0
+    }
+
+    pub fn borrow_factor_multiplier(&self) -> Option<Fraction> {
+        (self.borrow_factor_multiplier_bps != 0)
+            .then(|| Fraction::from_bps(self.borrow_factor_multiplier_bps))
+    }
+
+    pub fn has_unprotected_lowest_liquidation_ltv_deposit(&self) -> bool {
+        self.has_unprotected_lowest_liquidation_ltv_deposit != 0
+    }
+
+    pub fn self_borrow_limit_value_f(&self) -> Option<Fraction> {
+        if self.self_borrow_limit_value == 0 {
+            None
+        } else {
+            Some(Fraction::from(self.self_borrow_limit_value))
+        }
+    }
 }
 
 pub struct InitObligationParams {
@@ -434,7 +622,11 @@ pub struct ObligationCollateral {
     pub deposited_amount: u64,
     pub market_value_sf: u128,
     pub borrowed_amount_against_this_collateral_in_elevation_group: u64,
-    pub padding: [u64; 9],
+    pub supply_only: u8,
+    /// Liquidated after the other collaterals sharing the lowest liquidation LTV.
+    pub protected: u8,
+    pub reserved: [u8; 6],
+    pub padding: [u64; 8],
 }
 
 impl ObligationCollateral {
@@ -444,10 +636,21 @@ impl ObligationCollateral {
             deposited_amount: 0,
             market_value_sf: 0,
             borrowed_amount_against_this_collateral_in_elevation_group: 0,
-            padding: [0; 9],
+            supply_only: 0,
+            protected: 0,
+            reserved: [0; 6],
+            padding: [0; 8],
         }
     }
 
+    pub fn is_supply_only(&self) -> bool {
+        self.supply_only != 0
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.protected != false as u8
+    }
+
     pub fn deposit(&mut self, collateral_amount: u64) -> Result<()> {
         self.deposited_amount = self
             .deposited_amount
@@ -530,3 +733,149 @@ impl ObligationLiquidity {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::borrow_rate_curve::BorrowRateCurve;
+
+    fn add_deposit(
+        obligation: &mut Obligation,
+        reserve: Pubkey,
+        status: ReserveStatus,
+    ) -> Result<&mut ObligationCollateral> {
+        obligation
+            .find_or_add_collateral_to_deposits(reserve, AssetTier::Regular, status, |_| Ok(()))
+    }
+
+    fn add_borrow(
+        obligation: &mut Obligation,
+        reserve: Pubkey,
+        status: ReserveStatus,
+    ) -> Result<(&mut ObligationLiquidity, usize)> {
+        obligation.find_or_add_liquidity_to_borrows(
+            reserve,
+            BigFraction::from_num(1),
+            AssetTier::Regular,
+            status,
+        )
+    }
+
+    #[test]
+    fn active_and_obsolete_reserves_open_new_positions() {
+        // Obsolete reserves are rejected by the instruction checks before reaching the obligation
+        for status in [ReserveStatus::Active, ReserveStatus::Obsolete] {
+            let mut obligation = Obligation::default();
+            let reserve = Pubkey::new_unique();
+
+            add_deposit(&mut obligation, reserve, status).unwrap();
+            add_borrow(&mut obligation, reserve, status).unwrap();
+
+            assert_eq!(obligation.deposits[0].deposit_reserve, reserve);
+            assert_eq!(obligation.borrows[0].borrow_reserve, reserve);
+        }
+    }
+
+    #[test]
+    fn hidden_reserve_cannot_open_new_positions() {
+        let mut obligation = Obligation::default();
+        let reserve = Pubkey::new_unique();
+
+        assert_eq!(
+            add_deposit(&mut obligation, reserve, ReserveStatus::Hidden).unwrap_err(),
+            LendingError::ReserveHidden.into()
+        );
+        assert_eq!(
+            add_borrow(&mut obligation, reserve, ReserveStatus::Hidden).unwrap_err(),
+            LendingError::ReserveHidden.into()
+        );
+        assert!(obligation.deposits_empty());
+        assert!(obligation.borrows_empty());
+    }
+
+    #[test]
+    fn hidden_reserve_keeps_existing_positions_usable() {
+        let mut obligation = Obligation::default();
+        let reserve = Pubkey::new_unique();
+        add_deposit(&mut obligation, reserve, ReserveStatus::Active).unwrap();
+        add_borrow(&mut obligation, reserve, ReserveStatus::Active).unwrap();
+
+        add_deposit(&mut obligation, reserve, ReserveStatus::Hidden)
+            .unwrap()
+            .deposit(10)
+            .unwrap();
+        let (_, borrow_index) =
+            add_borrow(&mut obligation, reserve, ReserveStatus::Hidden).unwrap();
+
+        assert_eq!(obligation.deposits[0].deposited_amount, 10);
+        assert_eq!(borrow_index, 0);
+    }
+
+    #[test]
+    fn supply_only_deposit_is_collateral_once_no_other_collateral_is_left() {
+        let mut obligation = Obligation::default();
+        let collateral_reserve = Pubkey::new_unique();
+        let supply_only_reserve = Pubkey::new_unique();
+        add_deposit(&mut obligation, collateral_reserve, ReserveStatus::Active).unwrap();
+        add_deposit(&mut obligation, supply_only_reserve, ReserveStatus::Active)
+            .unwrap()
+            .supply_only = true as u8;
+
+        // No debt to back
+        assert!(!obligation.is_supply_only_counted_as_collateral());
+        assert!(!obligation.is_counted_as_collateral(&obligation.deposits[1]));
+
+        add_borrow(&mut obligation, Pubkey::new_unique(), ReserveStatus::Active).unwrap();
+        assert!(!obligation.is_supply_only_counted_as_collateral());
+        assert!(obligation.is_counted_as_collateral(&obligation.deposits[0]));
+        assert!(!obligation.is_counted_as_collateral(&obligation.deposits[1]));
+
+        // The regular collateral was fully seized, only the supply-only deposit backs the debt
+        obligation.deposits[0] = ObligationCollateral::default();
+        assert!(obligation.is_supply_only_counted_as_collateral());
+        assert!(obligation.is_counted_as_collateral(&obligation.deposits[1]));
+    }
+
+    #[test]
+    fn weighted_average_borrow_rate_requires_every_borrow_reserve_once() {
+        let mut obligation = Obligation::default();
+        let mut reserve_a = Reserve::default();
+        reserve_a.config.borrow_rate_curve = BorrowRateCurve::new_flat(1_000);
+        let mut reserve_b = Reserve::default();
+        reserve_b.config.borrow_rate_curve = BorrowRateCurve::new_flat(4_000);
+        let (pk_a, pk_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        add_borrow(&mut obligation, pk_a, ReserveStatus::Active)
+            .unwrap()
+            .0
+            .market_value_sf = Fraction::from(300).to_bits();
+        add_borrow(&mut obligation, pk_b, ReserveStatus::Active)
+            .unwrap()
+            .0
+            .market_value_sf = Fraction::from(100).to_bits();
+
+        assert_eq!(
+            obligation
+                .calculate_weighted_average_borrow_rate([(pk_a, &reserve_a), (pk_b, &reserve_b)])
+                .unwrap()
+                .to_bps::<u64>()
+                .unwrap(),
+            1_750
+        );
+        assert_eq!(
+            obligation
+                .calculate_weighted_average_borrow_rate([(pk_a, &reserve_a)])
+                .unwrap_err(),
+            LendingError::InvalidAccountInput.into()
+        );
+        assert_eq!(
+            obligation
+                .calculate_weighted_average_borrow_rate([
+                    (pk_a, &reserve_a),
+                    (pk_a, &reserve_a),
+                    (pk_b, &reserve_b)
+                ])
+                .unwrap_err(),
+            LendingError::InvalidAccountInput.into()
+        );
+    }
+}
@@ -19,10 +19,12 @@ use super::{LastUpdate, TokenInfo};
 use crate::{
     fraction::FractionExtra,
     utils::{
-        borrow_rate_curve::BorrowRateCurve, BigFraction, Fraction, INITIAL_COLLATERAL_RATE,
-        PROGRAM_VERSION, RESERVE_CONFIG_SIZE, RESERVE_SIZE, SLOTS_PER_YEAR,
+        borrow_rate_curve::BorrowRateCurve, BigFraction, Fraction, ELEVATION_GROUP_NONE, FULL_BPS,
+        INITIAL_COLLATERAL_RATE, PROGRAM_VERSION, RESERVE_CONFIG_SIZE, RESERVE_SIZE,
+        SLOTS_PER_YEAR,
     },
-    CalculateBorrowResult, CalculateRepayResult, LendingError, LendingResult, ReferrerTokenState,
+    CalculateBorrowResult, CalculateRepayResult, ClaimableFees, ElevationGroup, LendingError,
+    LendingMarket, LendingResult, ReferrerTokenState,
 };
 
 #[derive(Default, Debug, PartialEq, Eq)]
@@ -77,14 +79,16 @@ pub struct Reserve {
     pub config: ReserveConfig,
 
     #[derivative(Debug = "ignore")]
-    pub config_padding: [u64; 117],
+    pub config_padding: [u64; 93],
 
     pub borrowed_amount_outside_elevation_group: u64,
 
     pub borrowed_amounts_against_this_reserve_in_elevation_groups: [u64; 32],
 
+    pub liquidator_allowlist: [Pubkey; 4],
+
     #[derivative(Debug = "ignore")]
-    pub padding: [u64; 207],
+    pub padding: [u64; 191],
 }
 
 impl Default for Reserve {
@@ -100,10 +104,11 @@ impl Default for Reserve {
             farm_debt: Pubkey::default(),
             reserve_liquidity_padding: [0; 150],
             reserve_collateral_padding: [0; 150],
-            config_padding: [0; 117],
+            config_padding: [0; 93],
             borrowed_amount_outside_elevation_group: 0,
             borrowed_amounts_against_this_reserve_in_elevation_groups: [0; 32],
-            padding: [0; 207],
+            liquidator_allowlist: [Pubkey::default(); 4],
+            padding: [0; 191],
         }
     }
 }
@@ -140,9 +145,39 @@ impl Reserve {
     pub fn current_borrow_rate(&self) -> Result<Fraction> {
         let utilization_rate = self.liquidity.utilization_rate()?;
 
-        self.config
+        let borrow_rate = self
+            .config
             .borrow_rate_curve
-            .get_borrow_rate(utilization_rate)
+            .get_borrow_rate(utilization_rate)?
+            .max(Fraction::from_bps(self.config.min_borrow_rate_bps));
+
+        if self.config.max_borrow_rate_bps > 0 {
+            Ok(borrow_rate.min(Fraction::from_bps(self.config.max_borrow_rate_bps)))
+        } else {
+            Ok(borrow_rate)
+        }
+    }
+
+    pub fn current_supply_rate(&self) -> Result<Fraction> {
+        let utilization_rate = self.liquidity.utilization_rate()?;
+        let protocol_take_rate =
+            Fraction::from_percent(self.config.get_protocol_take_rate_pct(utilization_rate));
+
+        Ok(self.current_borrow_rate()? * utilization_rate * (Fraction::ONE - protocol_take_rate))
+    }
+
+    pub fn current_borrow_apy(&self) -> Result<Fraction> {
+        let borrow_rate = self.current_borrow_rate()?
+            + Fraction::from_bps(self.config.host_fixed_interest_rate_bps);
+
+        Ok(approximate_compounded_interest(borrow_rate, SLOTS_PER_YEAR) - Fraction::ONE)
+    }
+
+    pub fn current_supply_apy(&self) -> Result<Fraction> {
+        Ok(
+            approximate_compounded_interest(self.current_supply_rate()?, SLOTS_PER_YEAR)
+                - Fraction::ONE,
+        )
     }
 
     pub fn borrow_factor_f(&self, is_in_elevation_group: bool) -> Fraction {
@@ -182,14 +217,33 @@ impl Reserve {
         Ok(collateral_amount)
     }
 
-    pub fn redeem_collateral(&mut self, collateral_amount: u64) -> Result<u64> {
+    pub fn redeem_collateral(
+        &mut self,
+        collateral_amount: u64,
+        charge_redeem_fee: bool,
+    ) -> Result<u64> {
         let collateral_exchange_rate = self.collateral_exchange_rate()?;
 
         let liquidity_amount = collateral_exchange_rate.collateral_to_liquidity(collateral_amount);
 
+        let redeem_fee = if charge_redeem_fee {
+            min(
+                self.config.calculate_redeem_fee(liquidity_amount),
+                liquidity_amount,
+            )
+        } else {
+            0
+        };
+        let liquidity_amount = liquidity_amount - redeem_fee;
+
         self.collateral.burn(collateral_amount)?;
         self.liquidity.withdraw(liquidity_amount)?;
 
+        if redeem_fee > 0 {
+            msg!("Redeem fee {} accrued as protocol fees", redeem_fee);
+            self.liquidity.accumulated_protocol_fees_sf += Fraction::from(redeem_fee).to_bits();
+        }
+
         Ok(liquidity_amount)
     }
 
@@ -203,14 +257,35 @@ impl Reserve {
         self.collateral.exchange_rate(total_liquidity)
     }
 
-    pub fn accrue_interest(&mut self, current_slot: Slot, referral_fee_bps: u16) -> Result<()> {
-        let slots_elapsed = self.last_update.slots_elapsed(current_slot)?;
+    /// Returns the slot up to which interest was accrued, earlier than `current_slot` when the
+    /// accrual was clamped. The remaining slots are accrued by the next refreshes.
+    pub fn accrue_interest(&mut self, current_slot: Slot, referral_fee_bps: u16) -> Result<Slot> {
+        let unclamped_slots_elapsed = self.last_update.slots_elapsed(current_slot)?;
+        let slots_elapsed = self
+            .config
+            .clamp_interest_accrual_slots(unclamped_slots_elapsed);
+        if slots_elapsed < unclamped_slots_elapsed {
+            msg!(
+                "Interest accrual clamped from {} to {} slots, the reserve must be refreshed again",
+                unclamped_slots_elapsed,
+                slots_elapsed
+            );
+        }
         if slots_elapsed > 0 {
             let current_borrow_rate = self.current_borrow_rate()?;
-            let protocol_take_rate = Fraction::from_percent(self.config.protocol_take_rate_pct);
+            let protocol_take_rate = Fraction::from_percent(
+                self.config
+                    .get_protocol_take_rate_pct(self.liquidity.utilization_rate()?),
+            );
             let referral_rate = Fraction::from_bps(referral_fee_bps);
             let host_fixed_interest_rate =
                 Fraction::from_bps(self.config.host_fixed_interest_rate_bps);
+            let supply_referral_rate = if self.config.supply_referrer != Pubkey::default() {
+                Fraction::from_bps(self.config.supply_referral_fee_bps)
+            } else {
+                Fraction::ZERO
+            };
+            let separate_host_fees = self.config.host_fee_receiver != Pubkey::default();
 
             self.liquidity.compound_interest(
                 current_borrow_rate,
@@ -218,10 +293,12 @@ impl Reserve {
                 slots_elapsed,
                 protocol_take_rate,
                 referral_rate,
+                supply_referral_rate,
+                separate_host_fees,
             )?;
         }
 
-        Ok(())
+        Ok(self.last_update.slot + slots_elapsed)
     }
 
     pub fn update_deposit_limit_crossed_slot(&mut self, current_slot: Slot) -> Result<()> {
@@ -271,6 +348,8 @@ impl Reserve {
                 FeeCalculation::Inclusive,
                 referral_fee_bps,
                 has_referrer,
+                self.config
+                    .get_borrow_fee_discount_bps(is_in_elevation_group),
             )?;
             let borrow_amount: u64 = borrow_amount_f.to_floor();
             let receive_amount = borrow_amount - borrow_fee - referrer_fee;
@@ -289,6 +368,8 @@ impl Reserve {
                 FeeCalculation::Exclusive,
                 referral_fee_bps,
                 has_referrer,
+                self.config
+                    .get_borrow_fee_discount_bps(is_in_elevation_group),
             )?;
 
             borrow_amount_f += Fraction::from_num(borrow_fee + referrer_fee);
@@ -337,13 +418,81 @@ impl Reserve {
         ))
     }
 
+    pub fn calculate_withdraw_host_fees(&self) -> u64 {
+        min(
+            self.liquidity.available_amount,
+            Fraction::from_bits(self.liquidity.accumulated_host_fees_sf).to_floor(),
+        )
+    }
+
+    pub fn is_liquidator_allowed(&self, liquidator: &Pubkey) -> bool {
+        let mut allowlist = self
+            .liquidator_allowlist
+            .iter()
+            .filter(|allowed| **allowed != Pubkey::default())
+            .peekable();
+        allowlist.peek().is_none() || allowlist.any(|allowed| allowed == liquidator)
+    }
+
+    pub fn get_active_elevation_groups<'a>(
+        &self,
+        reserve_pk: &Pubkey,
+        lending_market: &'a LendingMarket,
+    ) -> Result<Vec<(&'a ElevationGroup, ElevationGroupReserveRole)>> {
+        let mut active_elevation_groups = Vec::new();
+        for id in self.config.elevation_groups {
+            let Some(elevation_group) = lending_market.get_elevation_group(id)? else {
+                continue;
+            };
+            if elevation_group.id == ELEVATION_GROUP_NONE {
+                continue;
+            }
+            let role = if elevation_group.debt_reserve == *reserve_pk {
+                ElevationGroupReserveRole::Debt
+            } else {
+                ElevationGroupReserveRole::Collateral
+            };
+            active_elevation_groups.push((elevation_group, role));
+        }
+
+        Ok(active_elevation_groups)
+    }
+
+    pub fn deposit_limit_f(&self) -> Result<Fraction> {
+        self.limit_to_liquidity_amount(self.config.deposit_limit)
+    }
+
+    pub fn borrow_limit_f(&self) -> Result<Fraction> {
+        self.limit_to_liquidity_amount(self.config.borrow_limit)
+    }
+
+    fn limit_to_liquidity_amount(&self, limit: u64) -> Result<Fraction> {
+        if !self.config.are_limits_in_quote_value() || limit == u64::MAX {
+            return Ok(Fraction::from(limit));
+        }
+
+        let market_price_f = self.liquidity.get_market_price_f();
+        if market_price_f == Fraction::ZERO {
+            msg!("Cannot convert a quote value limit with a zero market price");
+            return err!(LendingError::InvalidOracleConfig);
+        }
+        let decimals = 10u64
+            .checked_pow(self.liquidity.mint_decimals as u32)
+            .ok_or(LendingError::MathOverflow)?;
+
+        let limit_f =
+            BigFraction::from_num(limit) * u128::from(decimals) / BigFraction::from(market_price_f);
+
+        Ok(Fraction::try_from(limit_f).unwrap_or(Fraction::MAX))
+    }
+
     pub fn deposit_limit_crossed(&self) -> Result<bool> {
-        let crossed = self.liquidity.total_supply()? > Fraction::from(self.config.deposit_limit);
+        let crossed = self.liquidity.total_supply()? > self.deposit_limit_f()?;
         Ok(crossed)
     }
 
     pub fn borrow_limit_crossed(&self) -> Result<bool> {
-        let crossed = self.liquidity.total_borrow() > Fraction::from(self.config.borrow_limit);
+        let crossed = self.liquidity.total_borrow() > self.borrow_limit_f()?;
         Ok(crossed)
     }
 
@@ -358,6 +507,25 @@ impl Reserve {
         let available_unclaimed: u64 = Fraction::from_bits(available_unclaimed_sf).to_floor();
         Ok(min(available_unclaimed, self.liquidity.available_amount))
     }
+
+    pub fn get_claimable_fees(&self) -> Result<ClaimableFees> {
+        let bounded_fees = |fees_sf: u128| -> u64 {
+            min(
+                Fraction::from_bits(fees_sf).to_floor(),
+                self.liquidity.available_amount,
+            )
+        };
+
+        Ok(ClaimableFees {
+            protocol_fees: self.calculate_redeem_fees()?,
+            referrer_fees: bounded_fees(self.liquidity.accumulated_referrer_fees_sf),
+            pending_referrer_fees: bounded_fees(self.liquidity.pending_referrer_fees_sf),
+            pending_supply_referrer_fees: bounded_fees(
+                self.liquidity.pending_supply_referrer_fees_sf,
+            ),
+            host_fees: self.calculate_withdraw_host_fees(),
+        })
+    }
 }
 
 pub struct InitReserveParams {
@@ -391,8 +559,14 @@ pub struct ReserveLiquidity {
     pub absolute_referral_rate_sf: u128,
     pub token_program: Pubkey,
 
-    pub padding2: [u64; 51],
-    pub padding3: [u128; 32],
+    pub consecutive_stale_price_refreshes: u64,
+    pub stale_price_borrowing_disabled: u8,
+    pub reserved: [u8; 7],
+
+    pub padding2: [u64; 49],
+    pub pending_supply_referrer_fees_sf: u128,
+    pub accumulated_host_fees_sf: u128,
+    pub padding3: [u128; 30],
 }
 
 impl Default for ReserveLiquidity {
@@ -414,8 +588,13 @@ impl Default for ReserveLiquidity {
             absolute_referral_rate_sf: 0,
             market_price_last_updated_ts: 0,
             token_program: Pubkey::default(),
-            padding2: [0; 51],
-            padding3: [0; 32],
+            consecutive_stale_price_refreshes: 0,
+            stale_price_borrowing_disabled: 0,
+            reserved: [0; 7],
+            padding2: [0; 49],
+            pending_supply_referrer_fees_sf: 0,
+            accumulated_host_fees_sf: 0,
+            padding3: [0; 30],
         }
     }
 }
@@ -439,8 +618,13 @@ impl ReserveLiquidity {
             absolute_referral_rate_sf: 0,
             market_price_last_updated_ts: 0,
             token_program: params.mint_token_program,
-            padding2: [0; 51],
-            padding3: [0; 32],
+            consecutive_stale_price_refreshes: 0,
+            stale_price_borrowing_disabled: 0,
+            reserved: [0; 7],
+            padding2: [0; 49],
+            pending_supply_referrer_fees_sf: 0,
+            accumulated_host_fees_sf: 0,
+            padding3: [0; 30],
         }
     }
 
@@ -449,7 +633,9 @@ impl ReserveLiquidity {
             Fraction::from(self.available_amount) + Fraction::from_bits(self.borrowed_amount_sf)
                 - Fraction::from_bits(self.accumulated_protocol_fees_sf)
                 - Fraction::from_bits(self.accumulated_referrer_fees_sf)
-                - Fraction::from_bits(self.pending_referrer_fees_sf),
+                - Fraction::from_bits(self.pending_referrer_fees_sf)
+                - Fraction::from_bits(self.pending_supply_referrer_fees_sf)
+                - Fraction::from_bits(self.accumulated_host_fees_sf),
         )
     }
 
@@ -529,12 +715,76 @@ impl ReserveLiquidity {
         Ok(())
     }
 
+    pub fn forgive_protocol_fees_dust(&mut self) {
+        let accumulated_protocol_fees_f = Fraction::from_bits(self.accumulated_protocol_fees_sf);
+        if accumulated_protocol_fees_f > Fraction::ZERO
+            && accumulated_protocol_fees_f < Fraction::ONE
+        {
+            msg!(
+                "Forgiving protocol fees dust {}",
+                accumulated_protocol_fees_f.to_display()
+            );
+            self.accumulated_protocol_fees_sf = 0;
+        }
+    }
+
+    pub fn withdraw_host_fees(&mut self, withdraw_amount: u64) -> Result<()> {
+        self.available_amount = self
+            .available_amount
+            .checked_sub(withdraw_amount)
+            .ok_or(LendingError::MathOverflow)?;
+        self.accumulated_host_fees_sf = Fraction::from_bits(self.accumulated_host_fees_sf)
+            .checked_sub(Fraction::from_num(withdraw_amount))
+            .ok_or_else(|| {
+                msg!("Accumulated host fees cannot be less than withdraw amount");
+                error!(LendingError::MathOverflow)
+            })?
+            .to_bits();
+
+        Ok(())
+    }
+
+    pub fn add_protocol_liquidity(&mut self, amount: u64) -> Result<()> {
+        self.available_amount = self
+            .available_amount
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        self.accumulated_protocol_fees_sf = Fraction::from_bits(self.accumulated_protocol_fees_sf)
+            .checked_add(Fraction::from_num(amount))
+            .ok_or(LendingError::MathOverflow)?
+            .to_bits();
+
+        Ok(())
+    }
+
     pub fn utilization_rate(&self) -> LendingResult<Fraction> {
+        self.utilization_rate_with_borrowed(Fraction::from_bits(self.borrowed_amount_sf))
+    }
+
+    pub fn utilization_rate_after_borrow(&self, amount: u64) -> LendingResult<Fraction> {
+        if amount > self.available_amount {
+            return Err(LendingError::InsufficientLiquidity);
+        }
+        let borrowed_amount_f =
+            Fraction::from_bits(self.borrowed_amount_sf) + Fraction::from(amount);
+        self.utilization_rate_with_borrowed(borrowed_amount_f)
+    }
+
+    pub fn utilization_rate_after_repay(&self, amount: u64) -> LendingResult<Fraction> {
+        let borrowed_amount_f =
+            Fraction::from_bits(self.borrowed_amount_sf).saturating_sub(Fraction::from(amount));
+        self.utilization_rate_with_borrowed(borrowed_amount_f)
+    }
+
+    fn utilization_rate_with_borrowed(
+        &self,
+        borrowed_amount_f: Fraction,
+    ) -> LendingResult<Fraction> {
         let total_supply = self.total_supply()?;
         if total_supply == Fraction::ZERO {
             return Ok(Fraction::ZERO);
         }
-        Ok(Fraction::from_bits(self.borrowed_amount_sf) / total_supply)
+        Ok(borrowed_amount_f / total_supply)
     }
 
     fn compound_interest(
@@ -544,6 +794,8 @@ impl ReserveLiquidity {
         slots_elapsed: u64,
         protocol_take_rate: Fraction,
         referral_rate: Fraction,
+        supply_referral_rate: Fraction,
+        separate_host_fees: bool,
     ) -> LendingResult<()> {
         let previous_cumulative_borrow_rate = BigFraction::from(self.cumulative_borrow_rate_bsf);
         let previous_debt_f = Fraction::from_bits(self.borrowed_amount_sf);
@@ -567,12 +819,24 @@ impl ReserveLiquidity {
         let variable_protocol_fee_f = net_new_variable_debt_f * protocol_take_rate;
         let absolute_referral_rate = protocol_take_rate * referral_rate;
         let max_referrers_fees_f = net_new_variable_debt_f * absolute_referral_rate;
+        let supply_referrer_fees_f =
+            (variable_protocol_fee_f - max_referrers_fees_f) * supply_referral_rate;
+
+        let (protocol_host_fee, separate_host_fee) = if separate_host_fees {
+            (Fraction::ZERO, fixed_host_fee)
+        } else {
+            (fixed_host_fee, Fraction::ZERO)
+        };
 
         let new_acc_protocol_fees_f =
-            acc_protocol_fees_f + fixed_host_fee + variable_protocol_fee_f - max_referrers_fees_f;
+            acc_protocol_fees_f + protocol_host_fee + variable_protocol_fee_f
+                - max_referrers_fees_f
+                - supply_referrer_fees_f;
 
         self.cumulative_borrow_rate_bsf = new_cumulative_borrow_rate.into();
         self.pending_referrer_fees_sf += max_referrers_fees_f.to_bits();
+        self.pending_supply_referrer_fees_sf += supply_referrer_fees_f.to_bits();
+        self.accumulated_host_fees_sf += separate_host_fee.to_bits();
         self.accumulated_protocol_fees_sf = new_acc_protocol_fees_f.to_bits();
         self.borrowed_amount_sf = new_debt_f.to_bits();
         self.absolute_referral_rate_sf = absolute_referral_rate.to_bits();
@@ -781,13 +1045,84 @@ pub struct ReserveConfig {
 
     pub utilization_limit_block_borrowing_above: u8,
 
-    #[cfg_attr(feature = "serde", serde(skip_serializing, default))]
-    #[derivative(Debug = "ignore")]
-    pub reserved_1: [u8; 2],
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub limits_in_quote_value: u8,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub disable_usage_as_collateral: u8,
 
     pub borrow_limit_outside_elevation_group: u64,
 
     pub borrow_limit_against_this_collateral_in_elevation_group: [u64; 32],
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub protocol_take_rate_bands: [ProtocolTakeRateBand; 4],
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_borrow_amount: u64,
+
+    /// Haircut on the collateral value counted towards the allowed borrow value.
+    /// Applied on top of the LTV in use, including an elevation group LTV,
+    /// but never to the liquidation threshold.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub borrow_collateral_haircut_bps: u64,
+
+    /// Cap on the collateral deposited by a single obligation, 0 means unlimited.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_deposit_per_obligation: u64,
+
+    /// Floor and ceiling applied to the borrow rate given by the curve, 0 ceiling means no clamp.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_borrow_rate_bps: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_borrow_rate_bps: u32,
+
+    /// Cap on the unclaimed referrer fees of the reserve, in liquidity tokens, 0 means unlimited.
+    /// Referrer fees above the cap are accrued as protocol fees.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_referrer_fees: u64,
+
+    /// Referrer credited with a share of the protocol's cut of the interest paid to suppliers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub supply_referrer: Pubkey,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub supply_referral_fee_bps: u64,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub elevation_group_origination_fee_discount_bps: u64,
+
+    /// Consecutive refreshes without a fully checked price after which borrowing is disabled
+    /// until re-enabled by the market owner, 0 means never.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_consecutive_stale_price_refreshes: u64,
+
+    /// Drop accumulated protocol fees below one token unit when redeeming fees,
+    /// instead of keeping them until they add up to a redeemable amount.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub forgive_protocol_fee_dust: u8,
+    #[cfg_attr(feature = "serde", serde(skip_serializing, default))]
+    #[derivative(Debug = "ignore")]
+    pub reserved_4: [u8; 7],
+
+    /// Token account receiving the host fixed interest, accrued separately from the protocol fees.
+    /// Default pubkey means the host fixed interest is accrued as protocol fees.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub host_fee_receiver: Pubkey,
+
+    /// Fee charged on the liquidity returned when redeeming collateral, accrued as protocol fees.
+    /// Not charged on liquidations nor when compounding collateral yield.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub redeem_fee_sf: u64,
+
+    /// Max number of slots of interest accrued in a single refresh, 0 means unclamped. The reserve
+    /// stays stale until enough refreshes have accrued all the elapsed slots.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_interest_accrual_slots: u64,
+
+    /// Max divergence between the stored price and the price of the new oracle feeds when
+    /// rotating the oracles of a priced reserve.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_oracle_rotation_price_divergence_bps: u64,
 }
 
 impl ReserveConfig {
@@ -795,6 +1130,27 @@ impl ReserveConfig {
         AssetTier::try_from(self.asset_tier).unwrap()
     }
 
+    pub fn clamp_interest_accrual_slots(&self, slots_elapsed: u64) -> u64 {
+        if self.max_interest_accrual_slots > 0 {
+            slots_elapsed.min(self.max_interest_accrual_slots)
+        } else {
+            slots_elapsed
+        }
+    }
+
+    pub fn calculate_redeem_fee(&self, liquidity_amount: u64) -> u64 {
+        (Fraction::from(liquidity_amount) * Fraction::from_bits(self.redeem_fee_sf.into()))
+            .to_ceil()
+    }
+
+    pub fn get_borrow_fee_discount_bps(&self, is_in_elevation_group: bool) -> u64 {
+        if is_in_elevation_group {
+            self.elevation_group_origination_fee_discount_bps
+        } else {
+            0
+        }
+    }
+
     pub fn get_borrow_factor(&self) -> Fraction {
         max(
             Fraction::ONE,
@@ -805,6 +1161,22 @@ impl ReserveConfig {
     pub fn status(&self) -> ReserveStatus {
         ReserveStatus::try_from(self.status).unwrap()
     }
+
+    pub fn get_protocol_take_rate_pct(&self, utilization_rate: Fraction) -> u8 {
+        let utilization_rate_bps: u32 = utilization_rate.to_bps().unwrap_or(u32::MAX);
+        self.protocol_take_rate_bands
+            .iter()
+            .filter(|band| band.is_set())
+            .take_while(|band| band.utilization_rate_bps <= utilization_rate_bps)
+            .last()
+            .map_or(self.protocol_take_rate_pct, |band| {
+                band.protocol_take_rate_pct
+            })
+    }
+
+    pub fn are_limits_in_quote_value(&self) -> bool {
+        self.limits_in_quote_value != 0
+    }
 }
 
 #[repr(u8)]
@@ -844,6 +1216,25 @@ pub struct WithdrawalCaps {
     pub config_interval_length_seconds: u64,
 }
 
+#[derive(BorshDeserialize, BorshSerialize, PartialEq, Eq, Default, Derivative)]
+#[derivative(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[zero_copy]
+#[repr(C)]
+pub struct ProtocolTakeRateBand {
+    pub utilization_rate_bps: u32,
+    pub protocol_take_rate_pct: u8,
+    #[cfg_attr(feature = "serde", serde(skip_serializing, default))]
+    #[derivative(Debug = "ignore")]
+    pub padding: [u8; 3],
+}
+
+impl ProtocolTakeRateBand {
+    pub fn is_set(&self) -> bool {
+        self.utilization_rate_bps != 0 || self.protocol_take_rate_pct != 0
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Default, PartialEq, Eq, Derivative)]
 #[derivative(Debug)]
 #[zero_copy]
@@ -851,8 +1242,7 @@ pub struct WithdrawalCaps {
 pub struct ReserveFees {
     pub borrow_fee_sf: u64,
     pub flash_loan_fee_sf: u64,
-    #[derivative(Debug = "ignore")]
-    pub padding: [u8; 8],
+    pub flash_loan_protocol_fee_sf: u64,
 }
 
 #[cfg(feature = "serde")]
@@ -876,6 +1266,7 @@ mod serde_reserve_fees {
             enum Field {
                 BorrowFee,
                 FlashLoanFee,
+                FlashLoanProtocolFee,
             }
 
             struct ReserveFeesVisitor;
@@ -896,10 +1287,11 @@ mod serde_reserve_fees {
                     let flash_loan_fee_sf = seq
                         .next_element()?
                         .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                    let flash_loan_protocol_fee_sf = seq.next_element()?.unwrap_or(0);
                     Ok(ReserveFees {
                         borrow_fee_sf,
                         flash_loan_fee_sf,
-                        padding: [0; 8],
+                        flash_loan_protocol_fee_sf,
                     })
                 }
 
@@ -909,6 +1301,7 @@ mod serde_reserve_fees {
                 {
                     let mut borrow_fee_f: Option<Fraction> = None;
                     let mut flash_loan_fee_f: Option<Fraction> = None;
+                    let mut flash_loan_protocol_fee_f: Option<Fraction> = None;
                     while let Some(key) = map.next_key()? {
                         match key {
                             Field::BorrowFee => {
@@ -942,6 +1335,14 @@ mod serde_reserve_fees {
                                     }
                                 }
                             }
+                            Field::FlashLoanProtocolFee => {
+                                if flash_loan_protocol_fee_f.is_some() {
+                                    return Err(de::Error::duplicate_field(
+                                        "flash_loan_protocol_fee",
+                                    ));
+                                }
+                                flash_loan_protocol_fee_f = Some(map.next_value()?);
+                            }
                         }
                     }
 
@@ -949,17 +1350,23 @@ mod serde_reserve_fees {
                         borrow_fee_f.ok_or_else(|| de::Error::missing_field("borrow_fee"))?;
                     let flash_loan_fee_f =
                         flash_loan_fee_f.unwrap_or(Fraction::from_bits(u64::MAX.into()));
+                    let flash_loan_protocol_fee_f = flash_loan_protocol_fee_f.unwrap_or_default();
                     Ok(ReserveFees {
                         borrow_fee_sf: u64::try_from(borrow_fee_f.to_bits())
                             .map_err(|_| de::Error::custom("borrow_fee does not fit in u64"))?,
                         flash_loan_fee_sf: u64::try_from(flash_loan_fee_f.to_bits())
                             .map_err(|_| de::Error::custom("flash_loan_fee does not fit in u64"))?,
-                        padding: [0; 8],
+                        flash_loan_protocol_fee_sf: u64::try_from(
+                            flash_loan_protocol_fee_f.to_bits(),
+                        )
+                        .map_err(|_| {
+                            de::Error::custom("flash_loan_protocol_fee does not fit in u64")
+                        })?,
                     })
                 }
             }
 
-            const FIELDS: &[&str] = &["borrow_fee", "flash_loan_fee"];
+            const FIELDS: &[&str] = &["borrow_fee", "flash_loan_fee", "flash_loan_protocol_fee"];
             deserializer.deserialize_struct("ReserveFees", FIELDS, ReserveFeesVisitor)
         }
     }
@@ -973,6 +1380,7 @@ mod serde_reserve_fees {
             struct ReserveFeesSerde {
                 borrow_fee: Fraction,
                 flash_loan_fee: String,
+                flash_loan_protocol_fee: Fraction,
             }
 
             let borrow_fee_f = Fraction::from_bits(self.borrow_fee_sf.into());
@@ -986,6 +1394,9 @@ mod serde_reserve_fees {
             let fees = ReserveFeesSerde {
                 borrow_fee: borrow_fee_f,
                 flash_loan_fee,
+                flash_loan_protocol_fee: Fraction::from_bits(
+                    self.flash_loan_protocol_fee_sf.into(),
+                ),
             };
             fees.serialize(serializer)
         }
@@ -999,10 +1410,15 @@ impl ReserveFees {
         fee_calculation: FeeCalculation,
         referral_fee_bps: u16,
         has_referrer: bool,
+        fee_discount_bps: u64,
     ) -> Result<(u64, u64)> {
+        let borrow_fee_sf = u128::from(self.borrow_fee_sf)
+            * u128::from(u64::from(FULL_BPS).saturating_sub(fee_discount_bps))
+            / u128::from(FULL_BPS);
+
         self.calculate_fees(
             borrow_amount,
-            self.borrow_fee_sf,
+            borrow_fee_sf as u64,
             fee_calculation,
             referral_fee_bps,
             has_referrer,
@@ -1023,6 +1439,13 @@ impl ReserveFees {
             has_referrer,
         )?;
 
+        let flash_loan_protocol_fee: u64 = (flash_loan_amount_f
+            * Fraction::from_bits(self.flash_loan_protocol_fee_sf.into()))
+        .to_ceil();
+        let protocol_fee = protocol_fee
+            .checked_add(flash_loan_protocol_fee)
+            .ok_or(LendingError::MathOverflow)?;
+
         Ok((protocol_fee, referral_fee))
     }
 
@@ -1080,6 +1503,12 @@ pub enum FeeCalculation {
     Inclusive,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ElevationGroupReserveRole {
+    Collateral,
+    Debt,
+}
+
 #[derive(
     AnchorSerialize,
     AnchorDeserialize,
@@ -1125,3 +1554,163 @@ pub fn approximate_compounded_interest(rate: Fraction, elapsed_slots: u64) -> Fr
 
     Fraction::ONE + first_term + second_term + third_term
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee_sf(fee: Fraction) -> u64 {
+        u64::try_from(fee.to_bits()).unwrap()
+    }
+
+    fn flash_loan_fees(flash_loan_fee: Fraction, flash_loan_protocol_fee: Fraction) -> ReserveFees {
+        ReserveFees {
+            flash_loan_fee_sf: fee_sf(flash_loan_fee),
+            flash_loan_protocol_fee_sf: fee_sf(flash_loan_protocol_fee),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flash_loan_protocol_fee_is_added_on_top_of_flash_loan_fee() {
+        let amount = Fraction::from_num(1_024_000);
+        let without_protocol_fee = flash_loan_fees(Fraction::from_num(1) / 128, Fraction::ZERO);
+        let with_protocol_fee =
+            flash_loan_fees(Fraction::from_num(1) / 128, Fraction::from_num(1) / 256);
+
+        let (protocol_fee_before, referral_fee_before) = without_protocol_fee
+            .calculate_flash_loan_fees(amount, 2_000, true)
+            .unwrap();
+        let (protocol_fee_after, referral_fee_after) = with_protocol_fee
+            .calculate_flash_loan_fees(amount, 2_000, true)
+            .unwrap();
+
+        assert_eq!(protocol_fee_before + referral_fee_before, 8_000);
+        assert_eq!(protocol_fee_after, protocol_fee_before + 4_000);
+        assert_eq!(referral_fee_after, referral_fee_before);
+    }
+
+    #[test]
+    fn flash_loan_protocol_fee_is_charged_without_flash_loan_fee() {
+        let fees = flash_loan_fees(Fraction::ZERO, Fraction::from_num(1) / 256);
+
+        let (protocol_fee, referral_fee) = fees
+            .calculate_flash_loan_fees(Fraction::from_num(1_000), 10_000, true)
+            .unwrap();
+
+        assert_eq!(protocol_fee, 4);
+        assert_eq!(referral_fee, 0);
+    }
+
+    fn borrow_fees(borrow_fee: Fraction) -> ReserveFees {
+        ReserveFees {
+            borrow_fee_sf: fee_sf(borrow_fee),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn borrow_fee_discount_reduces_protocol_and_referrer_fees() {
+        let fees = borrow_fees(Fraction::from_num(1) / 128);
+        let amount = Fraction::from_num(1_024_000);
+
+        let (protocol_fee, referral_fee) = fees
+            .calculate_borrow_fees(amount, FeeCalculation::Exclusive, 2_500, true, 0)
+            .unwrap();
+        assert_eq!(protocol_fee, 6_000);
+        assert_eq!(referral_fee, 2_000);
+
+        let (protocol_fee, referral_fee) = fees
+            .calculate_borrow_fees(amount, FeeCalculation::Exclusive, 2_500, true, 5_000)
+            .unwrap();
+        assert_eq!(protocol_fee, 3_000);
+        assert_eq!(referral_fee, 1_000);
+    }
+
+    #[test]
+    fn borrow_fee_discount_without_referrer_goes_to_protocol() {
+        let fees = borrow_fees(Fraction::from_num(1) / 128);
+
+        let (protocol_fee, referral_fee) = fees
+            .calculate_borrow_fees(
+                Fraction::from_num(1_024_000),
+                FeeCalculation::Exclusive,
+                2_500,
+                false,
+                5_000,
+            )
+            .unwrap();
+
+        assert_eq!(protocol_fee, 4_000);
+        assert_eq!(referral_fee, 0);
+    }
+
+    #[test]
+    fn full_borrow_fee_discount_waives_all_fees() {
+        let fees = borrow_fees(Fraction::from_num(1) / 128);
+
+        for fee_discount_bps in [10_000, 20_000] {
+            let (protocol_fee, referral_fee) = fees
+                .calculate_borrow_fees(
+                    Fraction::from_num(1_024_000),
+                    FeeCalculation::Exclusive,
+                    2_500,
+                    true,
+                    fee_discount_bps,
+                )
+                .unwrap();
+
+            assert_eq!(protocol_fee, 0);
+            assert_eq!(referral_fee, 0);
+        }
+    }
+
+    #[test]
+    fn redeem_fee_preserves_collateral_exchange_rate() {
+        let mut reserve = Reserve::default();
+        reserve.liquidity.available_amount = 1_000;
+        reserve.collateral.mint_total_supply = 500;
+        reserve.config.redeem_fee_sf = fee_sf(Fraction::from_num(1) / 128);
+        let exchange_rate_before = reserve.collateral_exchange_rate().unwrap();
+
+        let liquidity_amount = reserve.redeem_collateral(64, true).unwrap();
+
+        assert_eq!(liquidity_amount, 127);
+        assert_eq!(reserve.liquidity.available_amount, 873);
+        assert_eq!(
+            reserve.liquidity.accumulated_protocol_fees_sf,
+            Fraction::ONE.to_bits()
+        );
+        assert_eq!(
+            reserve.liquidity.total_supply().unwrap(),
+            Fraction::from_num(872)
+        );
+        assert_eq!(reserve.collateral.mint_total_supply, 436);
+        assert_eq!(
+            reserve.collateral_exchange_rate().unwrap(),
+            exchange_rate_before
+        );
+    }
+
+    #[test]
+    fn redeem_fee_is_not_charged_when_disabled() {
+        let mut reserve = Reserve::default();
+        reserve.liquidity.available_amount = 1_000;
+        reserve.collateral.mint_total_supply = 500;
+        reserve.config.redeem_fee_sf = fee_sf(Fraction::from_num(1) / 128);
+        let exchange_rate_before = reserve.collateral_exchange_rate().unwrap();
+
+        let liquidity_amount = reserve.redeem_collateral(64, false).unwrap();
+
+        assert_eq!(liquidity_amount, 128);
+        assert_eq!(reserve.liquidity.accumulated_protocol_fees_sf, 0);
+        assert_eq!(
+            reserve.liquidity.total_supply().unwrap(),
+            Fraction::from_num(872)
+        );
+        assert_eq!(
+            reserve.collateral_exchange_rate().unwrap(),
+            exchange_rate_before
+        );
+    }
+}
@@ -6,7 +6,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use serde;
 
 #[cfg(feature = "serde")]
-use super::serde_string;
+use super::{serde_string, serde_utf_string};
 use crate::{
     utils::{NULL_PUBKEY, TOKEN_INFO_SIZE},
     LendingError,
@@ -41,11 +41,22 @@ pub struct TokenInfo {
 
     pub block_price_usage: u8,
 
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub price_feed_priority: u8,
+
     #[cfg_attr(feature = "serde", serde(skip_serializing, default))]
-    pub reserved: [u8; 7],
+    pub reserved: [u8; 6],
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_age_price_grace_seconds: u64,
+
+    /// Currency the oracles quote the token price in, must match the lending market's quote
+    /// currency. Zeroed means unspecified, the oracles are then assumed to quote in the market's.
+    #[cfg_attr(feature = "serde", serde(with = "serde_utf_string", default))]
+    pub quote_currency: [u8; 32],
 
     #[cfg_attr(feature = "serde", serde(skip_serializing, default))]
-    pub _padding: [u64; 19],
+    pub _padding: [u64; 14],
 }
 
 impl std::fmt::Debug for TokenInfo {
@@ -61,6 +72,17 @@ impl std::fmt::Debug for TokenInfo {
             .field("switchboard_configuration", &self.switchboard_configuration)
             .field("pyth_configuration", &self.pyth_configuration)
             .field("block_price_usage", &self.block_price_usage)
+            .field("price_feed_priority", &self.price_feed_priority)
+            .field(
+                "max_age_price_grace_seconds",
+                &self.max_age_price_grace_seconds,
+            )
+            .field(
+                "quote_currency",
+                &std::str::from_utf8(&self.quote_currency)
+                    .unwrap_or("InvalidQuoteCurrency")
+                    .trim_end_matches('\0'),
+            )
             .finish()
     }
 }
@@ -74,6 +96,10 @@ impl TokenInfo {
         scope_prices_info: Option<&AccountInfo>,
     ) -> Result<()> {
         require!(self.is_valid(), LendingError::InvalidOracleConfig);
+        require!(
+            self.is_price_feed_priority_valid(),
+            LendingError::InvalidOracleConfig
+        );
         require!(self.is_twap_config_valid(), LendingError::InvalidTwapConfig);
         require!(
             self.check_pyth_acc_matches(pyth_info),
@@ -98,6 +124,21 @@ impl TokenInfo {
                 || self.pyth_configuration.is_enabled())
     }
 
+    #[inline]
+    pub fn is_price_feed_priority_valid(&self) -> bool {
+        match PriceFeedPriority::try_from(self.price_feed_priority) {
+            Ok(PriceFeedPriority::MostRecent) => true,
+            Ok(PriceFeedPriority::ScopeWithPythBackup) => {
+                self.scope_configuration.is_enabled() && self.pyth_configuration.is_enabled()
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn price_feed_priority(&self) -> PriceFeedPriority {
+        PriceFeedPriority::try_from(self.price_feed_priority).unwrap()
+    }
+
     #[inline]
     pub fn is_twap_enabled(&self) -> bool {
         self.max_twap_divergence_bps > 0
@@ -163,6 +204,12 @@ impl TokenInfo {
         }
     }
 
+    pub fn has_same_oracle_feeds(&self, other: &TokenInfo) -> bool {
+        self.scope_configuration == other.scope_configuration
+            && self.switchboard_configuration == other.switchboard_configuration
+            && self.pyth_configuration == other.pyth_configuration
+    }
+
     pub fn symbol(&self) -> &str {
         std::str::from_utf8(&self.name)
             .unwrap_or("InvalidTokenName")
@@ -170,6 +217,21 @@ impl TokenInfo {
     }
 }
 
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    Debug,
+    PartialEq,
+    Eq,
+    num_enum::IntoPrimitive,
+    num_enum::TryFromPrimitive,
+)]
+#[repr(u8)]
+pub enum PriceFeedPriority {
+    MostRecent = 0,
+    ScopeWithPythBackup = 1,
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
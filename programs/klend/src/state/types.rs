@@ -1,4 +1,7 @@
+use anchor_lang::prelude::*;
+
 use crate::{utils::Fraction, PriceStatusFlags};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CalculateBorrowResult {
     pub borrow_amount_f: Fraction,
@@ -19,6 +22,7 @@ pub struct CalculateLiquidationResult {
     pub repay_amount: u64,
     pub withdraw_amount: u64,
     pub liquidation_bonus_rate: Fraction,
+    pub liquidation_reason: LiquidationReason,
 }
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LiquidateObligationResult {
@@ -27,6 +31,7 @@ pub struct LiquidateObligationResult {
     pub withdraw_amount: u64,
     pub withdraw_collateral_amount: u64,
     pub liquidation_bonus_rate: Fraction,
+    pub liquidation_reason: LiquidationReason,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -35,11 +40,91 @@ pub struct LiquidateAndRedeemResult {
     pub withdraw_amount: u64,
     pub withdraw_collateral_amount: u64,
     pub total_withdraw_liquidity_amount: Option<(u64, u64)>,
+    pub liquidation_reason: LiquidationReason,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiquidationReason {
+    LtvExceeded,
+    AutoDeleveraging,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LiquidateAndRedeemReturnData {
+    pub repay_amount: u64,
+    pub withdraw_collateral_amount: u64,
+    pub protocol_liquidation_fee: u64,
+    pub net_withdraw_liquidity_amount: u64,
+    pub liquidation_reason: LiquidationReason,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DepositLiquidityResult {
+    pub collateral_amount: u64,
+    pub collateral_exchange_rate_sf: u128,
+    pub total_supply_sf: u128,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ObligationSnapshot {
+    pub deposited_value_sf: u128,
+    pub borrow_factor_adjusted_debt_value_sf: u128,
+    pub loan_to_value_sf: u128,
+    pub elevation_group: u8,
+    pub has_debt: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReserveVaultBalanceSnapshot {
+    pub vault_balance: u64,
+    pub available_amount: u64,
+    pub accumulated_protocol_fees: u64,
+    pub drift: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimableFees {
+    pub protocol_fees: u64,
+    pub referrer_fees: u64,
+    pub pending_referrer_fees: u64,
+    pub pending_supply_referrer_fees: u64,
+    pub host_fees: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorrowBlockedReason {
+    MarketBorrowingDisabled,
+    ReserveObsolete,
+    ReserveDeprecated,
+    ReserveStale,
+    ReserveHidden,
+    StalePriceBorrowingDisabled,
+    BorrowLimitReached,
+    UtilizationLimitReached,
+    InconsistentElevationGroup,
+    ElevationGroupNewLoansDisabled,
+    BorrowingDisabledOutsideElevationGroup,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReserveEffectiveRiskParams {
+    pub loan_to_value_pct: u8,
+    pub liquidation_threshold_pct: u8,
+    pub max_liquidation_bonus_bps: u16,
+    pub borrow_factor_f: Fraction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompoundYieldResult {
+    pub redeemed_collateral_amount: u64,
+    pub yield_liquidity_amount: u64,
+    pub minted_collateral_amount: u64,
 }
 
 pub struct LiquidationParams {
     pub user_ltv: Fraction,
     pub liquidation_bonus_rate: Fraction,
+    pub liquidation_reason: LiquidationReason,
 }
 
 pub struct RefreshObligationDepositsResult {
@@ -50,6 +135,9 @@ pub struct RefreshObligationDepositsResult {
     pub unhealthy_borrow_value_f: Fraction,
     pub prices_state: PriceStatusFlags,
     pub borrowing_disabled: bool,
+    pub has_zero_ltv_deposit: bool,
+    pub has_unprotected_lowest_liquidation_ltv_deposit: bool,
+    pub price_timestamps: PriceTimestampsRange,
 }
 
 pub struct RefreshObligationBorrowsResult {
@@ -58,6 +146,45 @@ pub struct RefreshObligationBorrowsResult {
     pub prices_state: PriceStatusFlags,
     pub highest_borrow_factor_pct: u64,
     pub borrowed_amount_in_elevation_group: Option<u64>,
+    pub price_timestamps: PriceTimestampsRange,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MarketTotals {
+    pub total_supply_value_f: Fraction,
+    pub total_borrow_value_f: Fraction,
+    pub utilization_rate_f: Fraction,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PriceTimestampsRange {
+    pub oldest: Option<u64>,
+    pub newest: Option<u64>,
+}
+
+impl PriceTimestampsRange {
+    pub fn include(&mut self, timestamp: u64) {
+        self.oldest = Some(self.oldest.map_or(timestamp, |ts| ts.min(timestamp)));
+        self.newest = Some(self.newest.map_or(timestamp, |ts| ts.max(timestamp)));
+    }
+
+    pub fn merge(self, other: Self) -> Self {
+        let mut merged = self;
+        if let Some(oldest) = other.oldest {
+            merged.include(oldest);
+        }
+        if let Some(newest) = other.newest {
+            merged.include(newest);
+        }
+        merged
+    }
+
+    pub fn divergence_secs(&self) -> u64 {
+        match (self.oldest, self.newest) {
+            (Some(oldest), Some(newest)) => newest.saturating_sub(oldest),
+            _ => 0,
+        }
+    }
 }
 
 pub enum LendingAction {
@@ -30,20 +30,7 @@ impl serde::Serialize for BorrowRateCurve {
     where
         S: serde::Serializer,
     {
-        let mut end_reached = false;
-        let points = self
-            .points
-            .iter()
-            .take_while(|p| {
-                if end_reached {
-                    return false;
-                } else if p.utilization_rate_bps == MAX_UTILIZATION_RATE_BPS {
-                    end_reached = true;
-                }
-                true
-            })
-            .collect::<Vec<_>>();
-        serde::Serialize::serialize(&points, serializer)
+        serde::Serialize::serialize(&self.control_points(), serializer)
     }
 }
 
@@ -180,6 +167,22 @@ impl BorrowRateCurve {
         Ok(curve)
     }
 
+    pub fn control_points(&self) -> Vec<CurvePoint> {
+        let mut end_reached = false;
+        self.points
+            .iter()
+            .take_while(|p| {
+                if end_reached {
+                    return false;
+                } else if p.utilization_rate_bps == MAX_UTILIZATION_RATE_BPS {
+                    end_reached = true;
+                }
+                true
+            })
+            .copied()
+            .collect()
+    }
+
     pub fn new_flat(borrow_rate_bps: u32) -> Self {
         let points = [
             CurvePoint {
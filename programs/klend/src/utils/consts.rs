@@ -39,7 +39,7 @@ pub const NULL_PUBKEY: solana_program::pubkey::Pubkey =
 pub const LENDING_MARKET_SIZE: usize = 4656;
 pub const RESERVE_SIZE: usize = 8616;
 pub const OBLIGATION_SIZE: usize = 3336;
-pub const RESERVE_CONFIG_SIZE: usize = 912;
+pub const RESERVE_CONFIG_SIZE: usize = 1104;
 pub const REFERRER_TOKEN_STATE_SIZE: usize = 352;
 pub const USER_METADATA_SIZE: usize = 1024;
 pub const REFERRER_STATE_SIZE: usize = 64;
@@ -56,6 +56,18 @@ pub const ELEVATION_GROUP_NONE: u8 = 0;
 
 pub const MAX_NUM_ELEVATION_GROUPS: u8 = 32;
 
+pub const MAX_NUM_ELEVATION_GROUPS_PER_RESERVE: u8 = 20;
+
+/// Bounds of the per-obligation borrow factor multiplier set by the risk council. Below 100% the
+/// obligation's debt is weighted less than the reserve's borrow factor, so the lower bound is kept
+/// close to 100%. The effective borrow factor never goes below 100%.
+pub const MIN_OBLIGATION_BORROW_FACTOR_MULTIPLIER_BPS: u64 = 9_000;
+pub const MAX_OBLIGATION_BORROW_FACTOR_MULTIPLIER_BPS: u64 = 50_000;
+
+/// Minimum grace period between a reserve crossing its deposit or borrow limit and the start of
+/// deleveraging liquidations against it, enforced when a reserve's margin call period is updated.
+pub const MIN_DELEVERAGING_MARGIN_CALL_PERIOD_SECS: u64 = 60 * 60;
+
 pub const USD_DECIMALS: u32 = 6;
 
 pub const MIN_NET_VALUE_IN_OBLIGATION: Fraction = fraction!(0.000001);
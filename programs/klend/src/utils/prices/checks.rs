@@ -58,6 +58,7 @@ pub(super) fn get_validated_price(
             {
                 Ok(()) => {
                     price_status.set(PriceStatusFlags::TWAP_CHECKED, true);
+                    price_status.set(PriceStatusFlags::TWAP_VERIFIED, true);
                 }
                 Err(e) => {
                     msg!("Price twap check failed token=[{price_label}]: {e:?}",);
@@ -12,7 +12,7 @@ use self::{
     checks::get_validated_price, pyth::get_pyth_price_and_twap, scope::get_scope_price_and_twap,
     switchboard::get_switchboard_price_and_twap, types::TimestampedPriceWithTwap,
 };
-use crate::{utils::Fraction, LendingError, PriceStatusFlags, TokenInfo};
+use crate::{utils::Fraction, LendingError, PriceFeedPriority, PriceStatusFlags, TokenInfo};
 
 const MAX_CONFIDENCE_PERCENTAGE: u64 = 2u64;
 
@@ -33,6 +33,15 @@ pub fn get_price(
     scope_prices_info: Option<&AccountInfo>,
     unix_timestamp: clock::UnixTimestamp,
 ) -> Result<Option<GetPriceResult>> {
+    if token_info.price_feed_priority() == PriceFeedPriority::ScopeWithPythBackup {
+        return get_scope_price_with_pyth_backup(
+            token_info,
+            pyth_price_account_info,
+            scope_prices_info,
+            unix_timestamp,
+        );
+    }
+
     let price = get_most_recent_price_and_twap(
         token_info,
         pyth_price_account_info,
@@ -44,6 +53,37 @@ pub fn get_price(
     Ok(get_validated_price(price, token_info, unix_timestamp))
 }
 
+fn get_scope_price_with_pyth_backup(
+    token_info: &TokenInfo,
+    pyth_price_account_info: Option<&AccountInfo>,
+    scope_prices_info: Option<&AccountInfo>,
+    unix_timestamp: clock::UnixTimestamp,
+) -> Result<Option<GetPriceResult>> {
+    let primary_price = scope_prices_info
+        .and_then(|a| get_scope_price_and_twap(a, &token_info.scope_configuration).ok())
+        .and_then(|price| get_validated_price(price, token_info, unix_timestamp));
+
+    if let Some(primary_price) = &primary_price {
+        if primary_price.status.contains(PriceStatusFlags::ALL_CHECKS) {
+            return Ok(Some(primary_price.clone()));
+        }
+    }
+
+    msg!("Primary scope price is stale or invalid, trying pyth backup");
+
+    let backup_price = pyth_price_account_info
+        .and_then(|a| get_pyth_price_and_twap(a).ok())
+        .and_then(|price| get_validated_price(price, token_info, unix_timestamp));
+
+    match (primary_price, backup_price) {
+        (_, Some(backup_price)) if backup_price.status.contains(PriceStatusFlags::ALL_CHECKS) => {
+            Ok(Some(backup_price))
+        }
+        (Some(primary_price), _) => Ok(Some(primary_price)),
+        (None, backup_price) => Ok(backup_price),
+    }
+}
+
 fn get_most_recent_price_and_twap(
     token_info: &TokenInfo,
     pyth_price_account_info: Option<&AccountInfo>,
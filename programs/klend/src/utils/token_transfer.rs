@@ -349,3 +349,49 @@ pub fn withdraw_fees_from_reserve<'a>(
 
     Ok(())
 }
+
+pub fn rebalance_reserve_liquidity_transfer<'a>(
+    token_program: AccountInfo<'a>,
+    reserve_liquidity_mint: AccountInfo<'a>,
+    source_reserve_supply_liquidity: AccountInfo<'a>,
+    destination_reserve_supply_liquidity: AccountInfo<'a>,
+    lending_market_authority: AccountInfo<'a>,
+    authority_signer_seeds: &[&[u8]],
+    amount: u64,
+    mint_decimals: u8,
+) -> Result<()> {
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program,
+            token_interface::TransferChecked {
+                from: source_reserve_supply_liquidity,
+                to: destination_reserve_supply_liquidity,
+                authority: lending_market_authority,
+                mint: reserve_liquidity_mint,
+            },
+            &[authority_signer_seeds],
+        ),
+        amount,
+        mint_decimals,
+    )?;
+
+    Ok(())
+}
+
+pub fn restake_referrer_fees_transfer<'a>(
+    collateral_token_program: AccountInfo<'a>,
+    collateral_mint: AccountInfo<'a>,
+    lending_market_authority: AccountInfo<'a>,
+    destination_collateral: AccountInfo<'a>,
+    authority_signer_seeds: &[&[u8]],
+    collateral_mint_amount: u64,
+) -> Result<()> {
+    spltoken::mint(
+        collateral_token_program,
+        collateral_mint,
+        lending_market_authority,
+        destination_collateral,
+        authority_signer_seeds,
+        collateral_mint_amount,
+    )
+}